@@ -0,0 +1,287 @@
+//! Moving old and reverted memories out of CLAUDE.md
+//!
+//! Every line of a CLAUDE.md costs context, so synced memories that were
+//! reverted, or are older than the retention period, are moved to dated
+//! archive files next to it (or deleted).
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context};
+use chrono::{DateTime, FixedOffset, Utc};
+use git2::Repository;
+use crate::config::CleanupConfig;
+use crate::memory::{entry_id, entry_is_retracted, find_memories_section, read_claude_md, rewrite_memory_entries};
+
+/// What happens to cleaned-up memories
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum CleanupMode {
+    /// Move them to an archive file next to the CLAUDE.md
+    #[default]
+    Archive,
+    /// Drop them
+    Delete,
+}
+
+/// Which memories leave CLAUDE.md, and where they go
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CleanupPolicy {
+    pub mode: CleanupMode,
+    /// Memories from commits older than this many days are cleaned up;
+    /// without it only reverted memories are
+    pub retention_days: Option<u32>,
+    /// One archive file per month (`CLAUDE.archive/2026-10.md`) rather than
+    /// a single `CLAUDE.archive.md`
+    pub dated: bool,
+}
+
+impl CleanupPolicy {
+    /// Build the policy from the `[cleanup]` section
+    pub fn from_config(config: &CleanupConfig) -> Self {
+        CleanupPolicy {
+            mode: match config.mode.as_deref() {
+                Some("delete") => CleanupMode::Delete,
+                None | Some("archive") => CleanupMode::Archive,
+                Some(other) => {
+                    eprintln!("⚠️  Unknown cleanup mode {:?}, archiving instead (expected \"archive\" or \"delete\")", other);
+                    CleanupMode::Archive
+                }
+            },
+            retention_days: config.retention_days,
+            dated: config.archive_format.as_deref().is_none_or(|format| format == "dated"),
+        }
+    }
+}
+
+/// Why a memory was cleaned up
+#[derive(Debug, Clone, PartialEq)]
+pub enum CleanupReason {
+    /// Struck through after its commit was reverted
+    Reverted,
+    /// Its commit is older than the retention period
+    Expired { days: i64 },
+}
+
+/// A memory taken out of a CLAUDE.md
+#[derive(Debug, Clone)]
+pub struct CleanedMemory {
+    /// CLAUDE.md it was in
+    pub path: PathBuf,
+    /// Hidden ID, recorded so later syncs don't write the memory back
+    pub id: String,
+    pub reason: CleanupReason,
+    /// The entry as it was in the file
+    pub lines: Vec<String>,
+    /// Time of its commit, when the repository still has it
+    pub committed: Option<DateTime<FixedOffset>>,
+}
+
+/// Clean up every CLAUDE.md in the project
+///
+/// Only synced entries inside the managed block are considered, and only
+/// those with a hidden ID: entries synced by older versions can't be retired,
+/// so a later sync would write them back. Returns the memories taken out;
+/// nothing is written on a dry run.
+pub fn cleanup_project(project_root: &Path, policy: &CleanupPolicy, dry_run: bool) -> Result<Vec<CleanedMemory>> {
+    let repo = Repository::discover(project_root).ok();
+    let mut cleaned = Vec::new();
+
+    for path in find_claude_md_files(project_root, repo.as_ref())? {
+        cleaned.extend(cleanup_file(&path, policy, repo.as_ref(), Utc::now(), dry_run)?);
+    }
+
+    Ok(cleaned)
+}
+
+/// Clean up one CLAUDE.md, archiving its cleaned memories unless deleting
+fn cleanup_file(
+    path: &Path,
+    policy: &CleanupPolicy,
+    repo: Option<&Repository>,
+    now: DateTime<Utc>,
+    dry_run: bool
+) -> Result<Vec<CleanedMemory>> {
+    let content = read_claude_md(path)?;
+    if find_memories_section(&content).is_none() {
+        return Ok(Vec::new());
+    }
+
+    let mut cleaned = Vec::new();
+    let (new_content, _) = rewrite_memory_entries(&content, |entry| {
+        let (sha, index) = entry_id(entry)?;
+        let committed = repo.and_then(|repo| commit_time(repo, sha));
+
+        let reason = if entry_is_retracted(entry) {
+            CleanupReason::Reverted
+        } else {
+            let days = (now - committed?.with_timezone(&Utc)).num_days();
+            if days <= i64::from(policy.retention_days?) {
+                return None;
+            }
+            CleanupReason::Expired { days }
+        };
+
+        cleaned.push(CleanedMemory {
+            path: path.to_path_buf(),
+            id: format!("{}:{}", sha, index),
+            reason,
+            lines: entry.iter().map(|line| line.to_string()).collect(),
+            committed,
+        });
+        Some(Vec::new())
+    });
+
+    if cleaned.is_empty() || dry_run {
+        return Ok(cleaned);
+    }
+
+    if policy.mode == CleanupMode::Archive {
+        archive(path, &cleaned, policy, now)?;
+    }
+    fs::write(path, new_content)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    Ok(cleaned)
+}
+
+/// Commit time of a SHA, if the repository has the commit
+fn commit_time(repo: &Repository, sha: &str) -> Option<DateTime<FixedOffset>> {
+    let commit = repo.revparse_single(sha).ok()?.peel_to_commit().ok()?;
+    let time = commit.time();
+    let offset = FixedOffset::east_opt(time.offset_minutes() * 60)?;
+    DateTime::from_timestamp(time.seconds(), 0).map(|t| t.with_timezone(&offset))
+}
+
+/// Archive file for a memory: by its commit's month when dated
+pub fn archive_path(path: &Path, policy: &CleanupPolicy, month: &str) -> PathBuf {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    if policy.dated {
+        dir.join("CLAUDE.archive").join(format!("{}.md", month))
+    } else {
+        dir.join("CLAUDE.archive.md")
+    }
+}
+
+/// Append cleaned memories to their archive files
+fn archive(path: &Path, cleaned: &[CleanedMemory], policy: &CleanupPolicy, now: DateTime<Utc>) -> Result<()> {
+    let mut by_file: BTreeMap<PathBuf, Vec<&CleanedMemory>> = BTreeMap::new();
+    for memory in cleaned {
+        let month = memory.committed
+            .map(|t| t.format("%Y-%m").to_string())
+            .unwrap_or_else(|| now.format("%Y-%m").to_string());
+        by_file.entry(archive_path(path, policy, &month)).or_default().push(memory);
+    }
+
+    for (archive_path, memories) in by_file {
+        let mut content = read_claude_md(&archive_path)?;
+        if content.is_empty() {
+            content = "# Archived memories\n\nMemories moved out of CLAUDE.md by `synaptic cleanup`.\n".to_string();
+        }
+        content.push_str(&format!("\n## Archived {}\n\n", now.format("%Y-%m-%d")));
+        for memory in memories {
+            content.push_str(&memory.lines.join("\n"));
+            content.push('\n');
+        }
+
+        if let Some(parent) = archive_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&archive_path, content)
+            .with_context(|| format!("Failed to write {}", archive_path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Every CLAUDE.md in the project, skipping hidden and git-ignored directories
+pub(crate) fn find_claude_md_files(project_root: &Path, repo: Option<&Repository>) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![project_root.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            if path.is_dir() {
+                let ignored = repo.is_some_and(|repo| {
+                    path.strip_prefix(repo.workdir().unwrap_or(project_root))
+                        .is_ok_and(|relative| repo.is_path_ignored(relative).unwrap_or(false))
+                });
+                if !name.starts_with('.') && name != "CLAUDE.archive" && !ignored {
+                    dirs.push(path);
+                }
+            } else if name == "CLAUDE.md" {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Signature, Time};
+
+    #[test]
+    fn test_cleanup_archives_by_month() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let repo = Repository::init(root).unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let commit_at = |seconds: i64, message: &str, parents: &[&git2::Commit]| {
+            let sig = Signature::new("Ada", "ada@acme.com", &Time::new(seconds, 0)).unwrap();
+            repo.commit(Some("HEAD"), &sig, &sig, message, &tree, parents).unwrap()
+        };
+        let now = DateTime::parse_from_rfc3339("2026-10-16T12:00:00Z").unwrap().with_timezone(&Utc);
+        let old = commit_at(now.timestamp() - 90 * 86400, "learned: old\n\nMemory: Old", &[]);
+        let old_commit = repo.find_commit(old).unwrap();
+        let recent = commit_at(now.timestamp() - 86400, "learned: recent\n\nMemory: Recent", &[&old_commit]);
+
+        let path = root.join("CLAUDE.md");
+        let content = format!(
+            "# CLAUDE.md\n\nIntro.\n\n<!-- synaptic:begin -->\n## SVCMS Memories\n\n\
+- Recent: learned `learned: recent` ({}) <!-- synaptic:{}:0 -->\n\
+- Old: learned `learned: old` ({}) <!-- synaptic:{}:0 -->\n\
+- ~~Gone: learned `learned: gone` (abcdef1)~~ *(reverted in 9999999)* <!-- synaptic:abcdef1:0 -->\n\
+- Legacy: learned `learned: old` ({}) by Ada\n\
+- Hand-written note\n<!-- synaptic:end -->\n",
+            &recent.to_string()[..7], recent, &old.to_string()[..7], old, &old.to_string()[..7]
+        );
+        fs::write(&path, &content).unwrap();
+
+        let policy = CleanupPolicy { retention_days: Some(30), dated: true, ..Default::default() };
+        let cleaned = cleanup_file(&path, &policy, Some(&repo), now, true).unwrap();
+        assert_eq!(cleaned.len(), 2);
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+
+        let cleaned = cleanup_file(&path, &policy, Some(&repo), now, false).unwrap();
+        assert_eq!(cleaned[0].reason, CleanupReason::Expired { days: 90 });
+        assert_eq!(cleaned[0].id, format!("{}:0", old));
+        assert_eq!(cleaned[1].reason, CleanupReason::Reverted);
+
+        let remaining = fs::read_to_string(&path).unwrap();
+        assert!(remaining.contains("- Recent:"));
+        assert!(remaining.contains("- Hand-written note\n<!-- synaptic:end -->\n"));
+        // Without a hidden ID the memory couldn't be retired, so it stays
+        assert!(remaining.contains("- Legacy:"));
+        assert!(!remaining.contains("- Old") && !remaining.contains("Gone"));
+
+        let july = fs::read_to_string(root.join("CLAUDE.archive/2026-07.md")).unwrap();
+        assert!(july.starts_with("# Archived memories\n"));
+        assert!(july.contains("## Archived 2026-10-16\n\n- Old: learned"));
+        // The reverted commit isn't in the repository, so it goes under this month
+        let october = fs::read_to_string(root.join("CLAUDE.archive/2026-10.md")).unwrap();
+        assert!(october.contains("- ~~Gone"));
+
+        // Delete mode writes no archive
+        fs::write(&path, &content).unwrap();
+        let policy = CleanupPolicy { mode: CleanupMode::Delete, ..policy };
+        fs::remove_dir_all(root.join("CLAUDE.archive")).unwrap();
+        assert_eq!(cleanup_file(&path, &policy, Some(&repo), now, false).unwrap().len(), 2);
+        assert!(!root.join("CLAUDE.archive").exists());
+    }
+}
@@ -19,6 +19,19 @@ pub struct CommitTypeValidator {
     aliases: HashMap<String, String>,
 }
 
+/// Why a commit type was rejected by the validator
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeRejection {
+    /// The type is not in any category, legacy list or scope custom types
+    UnknownType { commit_type: String },
+    /// The category of a `category.type` header doesn't exist
+    UnknownCategory { category: String },
+    /// The category exists but doesn't contain the type
+    TypeNotInCategory { category: String, commit_type: String },
+    /// The scope is configured and doesn't allow the type's category
+    CategoryNotAllowed { scope: String, category: String },
+}
+
 /// Parsed commit type representation
 #[derive(Debug, Clone, PartialEq)]
 pub struct ParsedCommitType {
@@ -102,35 +115,63 @@ impl CommitTypeValidator {
     
     /// Validate a commit type with optional scope
     pub fn is_valid(&self, commit_type_str: &str, scope: Option<&str>) -> bool {
+        self.check(commit_type_str, scope).is_ok()
+    }
+    
+    /// Validate a commit type with optional scope, explaining any rejection
+    pub fn check(&self, commit_type_str: &str, scope: Option<&str>) -> Result<(), TypeRejection> {
         let parsed = self.parse_commit_type(commit_type_str);
         
-        match (&parsed.category, &parsed.commit_type, scope) {
-            // Two-tier validation: category.type(scope)
-            (Some(category), commit_type, Some(scope)) => {
-                self.validate_two_tier(category, commit_type, scope)
-            }
-            
-            // Two-tier without scope: category.type
-            (Some(category), commit_type, None) => {
-                self.validate_category_type(category, commit_type)
+        match (parsed.category, parsed.commit_type, scope) {
+            // Two-tier validation: category.type(scope) or category.type
+            (Some(category), commit_type, scope) => {
+                self.check_two_tier(category, commit_type, scope)
             }
             
             // Legacy validation: type(scope) or just type
-            (None, commit_type, _) => {
-                self.validate_legacy(commit_type)
+            (None, commit_type, scope) => {
+                self.check_legacy(commit_type, scope)
             }
         }
     }
     
-    /// Validate two-tier format with scope
-    fn validate_two_tier(&self, category: &str, commit_type: &str, scope: &str) -> bool {
+    /// Find the category a single-tier type belongs to
+    pub fn category_for(&self, commit_type: &str) -> Option<String> {
+        let mut matching: Vec<&String> = self.categories.iter()
+            .filter(|(_, types)| types.contains(commit_type))
+            .map(|(category, _)| category)
+            .collect();
+        
+        // Several categories may share a type; pick one deterministically
+        matching.sort();
+        matching.first().map(|category| category.to_string())
+    }
+    
+    /// Validate two-tier format, checking the scope when given
+    fn check_two_tier(&self, category: String, commit_type: String, scope: Option<&str>) -> Result<(), TypeRejection> {
+        // Scope-specific custom types: <scope>.<custom_type>(<subscope>)
+        if scope.is_some() && self.is_custom_type(&category, &commit_type) {
+            return Ok(());
+        }
+        
         // First, check if the category.type combination is valid
-        if !self.validate_category_type(category, commit_type) {
-            return false;
+        if !self.validate_category_type(&category, &commit_type) {
+            let known_category = self.categories.contains_key(&category) ||
+                self.find_scope_config(&category).is_some();
+            return Err(if known_category {
+                TypeRejection::TypeNotInCategory { category, commit_type }
+            } else {
+                TypeRejection::UnknownCategory { category }
+            });
         }
         
         // Then, check if this scope allows this category
-        self.is_scope_category_allowed(scope, category)
+        match scope {
+            Some(scope) if !self.is_scope_category_allowed(scope, &category) => {
+                Err(TypeRejection::CategoryNotAllowed { scope: scope.to_string(), category })
+            }
+            _ => Ok(()),
+        }
     }
     
     /// Validate category.type combination
@@ -138,6 +179,14 @@ impl CommitTypeValidator {
         self.categories
             .get(category)
             .map(|types| types.contains(commit_type))
+            .unwrap_or(false) ||
+        self.is_custom_type(category, commit_type)
+    }
+    
+    /// Check if a type is a custom type declared by a scope
+    fn is_custom_type(&self, scope: &str, commit_type: &str) -> bool {
+        self.find_scope_config(scope)
+            .map(|config| config.custom_types.iter().any(|t| t == commit_type))
             .unwrap_or(false)
     }
     
@@ -163,15 +212,67 @@ impl CommitTypeValidator {
     }
     
     /// Legacy validation for backwards compatibility
-    fn validate_legacy(&self, commit_type: &str) -> bool {
+    fn check_legacy(&self, commit_type: String, scope: Option<&str>) -> Result<(), TypeRejection> {
         // Check legacy types
-        if self.legacy_types.contains(commit_type) {
-            return true;
+        if self.legacy_types.contains(&commit_type) {
+            return Ok(());
+        }
+        
+        // Configured scopes decide which categories and custom types they accept
+        if let Some(scope) = scope {
+            if self.find_scope_config(scope).is_some() {
+                let allowed = self.is_custom_type(scope, &commit_type) ||
+                    self.categories.iter()
+                        .filter(|(_, types)| types.contains(&commit_type))
+                        .any(|(category, _)| self.is_scope_category_allowed(scope, category));
+                
+                return match (allowed, self.category_for(&commit_type)) {
+                    (true, _) => Ok(()),
+                    (false, Some(category)) => Err(TypeRejection::CategoryNotAllowed {
+                        scope: scope.to_string(),
+                        category,
+                    }),
+                    (false, None) => Err(TypeRejection::UnknownType { commit_type }),
+                };
+            }
         }
         
         // Check if it's a type from any category (for backwards compatibility)
-        self.categories.values()
-            .any(|types| types.contains(commit_type))
+        if self.categories.values().any(|types| types.contains(&commit_type)) {
+            Ok(())
+        } else {
+            Err(TypeRejection::UnknownType { commit_type })
+        }
+    }
+    
+    /// All type names this validator knows: category types, legacy types,
+    /// aliases and scope custom types
+    pub fn known_types(&self) -> Vec<String> {
+        let scope_configs = [
+            &self.module_scopes,
+            &self.cross_cutting_scopes,
+            &self.tooling_scopes,
+            &self.project_wide_scopes,
+        ];
+        
+        let mut known: Vec<String> = self.categories.values().flatten().cloned()
+            .chain(self.legacy_types.iter().cloned())
+            .chain(self.aliases.keys().cloned())
+            .chain(scope_configs.iter()
+                .flat_map(|scopes| scopes.values())
+                .flat_map(|config| config.custom_types.iter().cloned()))
+            .collect();
+        
+        known.sort();
+        known.dedup();
+        known
+    }
+    
+    /// All category names, sorted
+    pub fn category_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.categories.keys().cloned().collect();
+        names.sort();
+        names
     }
     
     /// Get all valid commit types for a scope
@@ -188,7 +289,7 @@ impl CommitTypeValidator {
                     // Add all types from all categories
                     for types in self.categories.values() {
                         for commit_type in types {
-                            valid_types.push(format!("{}", commit_type));
+                            valid_types.push(commit_type.to_string());
                             // Also add two-tier format
                             for (cat_name, cat_types) in &self.categories {
                                 if cat_types.contains(commit_type) {
@@ -329,6 +430,51 @@ mod tests {
         assert!(validator.is_valid("discussed", None));
     }
     
+    #[test]
+    fn test_scope_custom_types_and_categories() {
+        let config: CommitTypesConfig = toml::from_str(r#"
+[scopes.modules]
+auth = { categories = ["standard", "knowledge"], custom_types = ["integrated"] }
+"#).unwrap();
+        let validator = CommitTypeValidator::from_config(&config);
+        
+        assert!(validator.is_valid("integrated", Some("auth")));
+        assert!(validator.is_valid("auth.integrated", Some("oauth")));
+        assert!(validator.is_valid("learned", Some("auth")));
+        assert!(!validator.is_valid("workflow", Some("auth")));
+        assert!(!validator.is_valid("meta.workflow", Some("auth")));
+        
+        assert_eq!(validator.category_for("learned"), Some("knowledge".to_string()));
+        assert_eq!(validator.category_for("unknown"), None);
+    }
+    
+    #[test]
+    fn test_check_explains_rejection() {
+        let config: CommitTypesConfig = toml::from_str(r#"
+[scopes.modules]
+auth = { categories = ["standard"], custom_types = [] }
+"#).unwrap();
+        let validator = CommitTypeValidator::from_config(&config);
+        
+        assert_eq!(validator.check("feat", Some("auth")), Ok(()));
+        assert_eq!(
+            validator.check("learnd", None),
+            Err(TypeRejection::UnknownType { commit_type: "learnd".to_string() })
+        );
+        assert_eq!(
+            validator.check("learned", Some("auth")),
+            Err(TypeRejection::CategoryNotAllowed { scope: "auth".to_string(), category: "knowledge".to_string() })
+        );
+        assert_eq!(
+            validator.check("knowledge.feat", None),
+            Err(TypeRejection::TypeNotInCategory { category: "knowledge".to_string(), commit_type: "feat".to_string() })
+        );
+        assert_eq!(
+            validator.check("wisdom.learned", None),
+            Err(TypeRejection::UnknownCategory { category: "wisdom".to_string() })
+        );
+    }
+    
     #[test]
     fn test_get_valid_types_for_scope() {
         let validator = CommitTypeValidator::default();
@@ -4,6 +4,7 @@ use std::path::{Path, PathBuf};
 use std::fs;
 use anyhow::{Result, Context, anyhow};
 use serde::{Deserialize, Serialize};
+use crate::commit_types::CommitTypeValidator;
 
 /// Synaptic configuration
 #[derive(Debug, Deserialize, Serialize)]
@@ -14,6 +15,7 @@ pub struct SynapticConfig {
     pub cleanup: Option<CleanupConfig>,
     pub query: Option<QueryConfig>,
     pub locations: Option<std::collections::HashMap<String, String>>,
+    pub trust: Option<TrustConfig>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -21,6 +23,22 @@ pub struct SyncConfig {
     pub default_depth: Option<usize>,
     pub auto_deduplicate: Option<bool>,
     pub dry_run: Option<bool>,
+    pub promote_breaking_changes: Option<bool>,
+    /// Issue tracker URL with a `{number}` placeholder, used to link `Refs: #87`
+    pub issue_url_template: Option<String>,
+    /// What happens to memories of reverted commits: "strike" (default) or "remove"
+    pub reverted_memories: Option<String>,
+    /// Extract each commit's changed files (slower; used by Obsidian notes and locations)
+    pub changed_files: Option<bool>,
+    /// Where memories without a Location go: "scope" (default), "common-directory"
+    /// or "nearest-claude-md"
+    pub location_strategy: Option<String>,
+    /// Which merge commits to read: "all" (default), "first-parent" or "ignore"
+    pub merges: Option<String>,
+    /// Read each `* type(scope): ...` bullet of a squash commit as its own commit
+    pub explode_squash: Option<bool>,
+    /// Notes ref holding SVCMS trailers added after the fact (default: refs/notes/svcms)
+    pub notes_ref: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -62,6 +80,10 @@ pub struct CommitTypeCategory {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CommitTypeScopesConfig {
+    /// CLAUDE.md (or directory) for `cross_cutting` scopes (default: root)
+    pub cross_cutting_target: Option<String>,
+    /// CLAUDE.md (or directory) for `tooling` scopes (default: root)
+    pub tooling_target: Option<String>,
     pub modules: Option<std::collections::HashMap<String, ScopeConfig>>,
     pub cross_cutting: Option<std::collections::HashMap<String, ScopeConfig>>,
     pub tooling: Option<std::collections::HashMap<String, ScopeConfig>>,
@@ -72,6 +94,8 @@ pub struct CommitTypeScopesConfig {
 pub struct ScopeConfig {
     pub categories: Vec<String>, // "all" is special value meaning all categories
     pub custom_types: Vec<String>,
+    /// Where this scope's memories go; modules default to `src/<scope>`
+    pub path: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -86,7 +110,27 @@ pub struct CleanupConfig {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ValidationConfig {
     pub check_frequency: Option<String>,
+    /// Make `synaptic validate` fail when it finds stale memories
     pub report_stale_memories: Option<bool>,
+    /// Age in days after which a memory is checked for churn (default: 180)
+    pub stale_after_days: Option<u32>,
+    /// Later commits to a memory's files that make an old memory stale (default: 10)
+    pub churn_threshold: Option<usize>,
+}
+
+/// Who may write memories; see `trust::TrustPolicy`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TrustConfig {
+    /// Require a valid GPG or SSH signature on memory-bearing commits
+    pub require_signature: Option<bool>,
+    /// SSH allowed-signers file, in git's `gpg.ssh.allowedSignersFile` format
+    pub allowed_signers: Option<String>,
+    /// Trusted author names or emails; `*@example.com` allows a whole domain
+    pub allowed_authors: Option<Vec<String>>,
+    /// What happens to untrusted memories: "quarantine" (default) or "skip"
+    pub untrusted: Option<String>,
+    /// Review file for quarantined memories (default: .synaptic/quarantine.md)
+    pub quarantine_file: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -98,12 +142,25 @@ pub struct QueryConfig {
 impl SynapticConfig {
     /// Load configuration with layering: global + project-specific
     pub fn load() -> Result<Self> {
+        let current_dir = std::env::current_dir()?;
+        Self::load_for_repo(&current_dir)
+    }
+    
+    /// Load layered configuration for the repository containing `repo_path`
+    pub fn load_for_repo(repo_path: &Path) -> Result<Self> {
+        Self::load_layered(&Self::default_config_path()?, repo_path)
+    }
+    
+    /// Load the global config at `global_path` and merge the project config
+    /// of the repository containing `repo_path` into it
+    fn load_layered(global_path: &Path, repo_path: &Path) -> Result<Self> {
         // Load global config first
-        let global_path = Self::default_config_path()?;
-        let mut config = Self::load_from(&global_path)?;
+        let mut config = Self::load_from(global_path)?;
         
-        // Try to load project config and merge
-        if let Ok(project_config) = Self::load_project_config() {
+        // Merge the project config, if any; a broken one is an error rather
+        // than silently ignored, or it would drop the global `[trust]` policy
+        if let Ok(git_root) = Self::find_git_root_from(repo_path) {
+            let project_config = Self::load_from(&git_root.join(".synaptic").join("config.toml"))?;
             config.merge(project_config);
         }
         
@@ -123,10 +180,10 @@ impl SynapticConfig {
         }
 
         let content = fs::read_to_string(path)
-            .context("Failed to read config file")?;
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
         
         let config: SynapticConfig = toml::from_str(&content)
-            .context("Failed to parse config file")?;
+            .with_context(|| format!("Failed to parse config file {}", path.display()))?;
         
         Ok(config)
     }
@@ -155,7 +212,12 @@ impl SynapticConfig {
     /// Find the git repository root
     fn find_git_root() -> Result<PathBuf> {
         let current_dir = std::env::current_dir()?;
-        let repo = git2::Repository::discover(&current_dir)
+        Self::find_git_root_from(&current_dir)
+    }
+    
+    /// Find the git repository root starting from a given path
+    fn find_git_root_from(path: &Path) -> Result<PathBuf> {
+        let repo = git2::Repository::discover(path)
             .context("Not in a git repository")?;
         
         repo.workdir()
@@ -177,6 +239,30 @@ impl SynapticConfig {
                 if other_sync.dry_run.is_some() {
                     sync.dry_run = other_sync.dry_run;
                 }
+                if other_sync.promote_breaking_changes.is_some() {
+                    sync.promote_breaking_changes = other_sync.promote_breaking_changes;
+                }
+                if other_sync.issue_url_template.is_some() {
+                    sync.issue_url_template = other_sync.issue_url_template;
+                }
+                if other_sync.reverted_memories.is_some() {
+                    sync.reverted_memories = other_sync.reverted_memories;
+                }
+                if other_sync.changed_files.is_some() {
+                    sync.changed_files = other_sync.changed_files;
+                }
+                if other_sync.location_strategy.is_some() {
+                    sync.location_strategy = other_sync.location_strategy;
+                }
+                if other_sync.merges.is_some() {
+                    sync.merges = other_sync.merges;
+                }
+                if other_sync.explode_squash.is_some() {
+                    sync.explode_squash = other_sync.explode_squash;
+                }
+                if other_sync.notes_ref.is_some() {
+                    sync.notes_ref = other_sync.notes_ref;
+                }
             } else {
                 self.sync = Some(other_sync);
             }
@@ -228,6 +314,14 @@ impl SynapticConfig {
                         commit_types.additional = Some(other_additional);
                     }
                 }
+                // Merge aliases (project entries win)
+                if let Some(other_aliases) = other_commit_types.aliases {
+                    if let Some(ref mut aliases) = commit_types.aliases {
+                        aliases.extend(other_aliases);
+                    } else {
+                        commit_types.aliases = Some(other_aliases);
+                    }
+                }
             } else {
                 self.commit_types = Some(other_commit_types);
             }
@@ -251,22 +345,142 @@ impl SynapticConfig {
                 self.locations = Some(other_locations);
             }
         }
+        
+        // Merge trust config: the project config is committed alongside the code
+        // it would vouch for, so it can tighten a global policy but not loosen it.
+        // Its signers file and quarantine file are ignored, since either could
+        // hand an attacker trust or a path into the agent's context.
+        if let Some(other_trust) = other.trust {
+            if let Some(ref mut trust) = self.trust {
+                if other_trust.require_signature == Some(true) {
+                    trust.require_signature = Some(true);
+                }
+                if trust.allowed_authors.as_ref().is_none_or(|authors| authors.is_empty()) {
+                    trust.allowed_authors = other_trust.allowed_authors;
+                }
+                if other_trust.untrusted.is_some() {
+                    trust.untrusted = other_trust.untrusted;
+                }
+            } else {
+                self.trust = Some(other_trust);
+            }
+        }
     }
 
-    /// Create default configuration
-    pub fn default() -> Self {
-        Self {
-            sync: Some(SyncConfig {
-                default_depth: Some(100),
-                auto_deduplicate: Some(true),
-                dry_run: Some(false),
-            }),
-            obsidian: None,
-            commit_types: None,
-            cleanup: None,
-            query: None,
-            locations: None,
-        }
+    /// Whether cherry-picked and rebased copies of a commit are merged (default: false)
+    pub fn auto_deduplicate(&self) -> bool {
+        self.sync
+            .as_ref()
+            .and_then(|s| s.auto_deduplicate)
+            .unwrap_or(false)
+    }
+
+    /// Whether breaking changes without a `Memory:` footer become memories (default: false)
+    pub fn promote_breaking_changes(&self) -> bool {
+        self.sync
+            .as_ref()
+            .and_then(|s| s.promote_breaking_changes)
+            .unwrap_or(false)
+    }
+
+    /// Issue tracker URL template for `#<number>` references, if configured
+    pub fn issue_url_template(&self) -> Option<String> {
+        self.sync
+            .as_ref()
+            .and_then(|s| s.issue_url_template.clone())
+    }
+
+    /// How memories of reverted commits are retracted, if configured
+    pub fn reverted_memories(&self) -> Option<&str> {
+        self.sync
+            .as_ref()
+            .and_then(|s| s.reverted_memories.as_deref())
+    }
+
+    /// Whether sync extracts each commit's changed files (default: false)
+    pub fn changed_files(&self) -> bool {
+        self.sync
+            .as_ref()
+            .and_then(|s| s.changed_files)
+            .unwrap_or(false)
+    }
+
+    /// Configured location strategy name, if any
+    pub fn location_strategy(&self) -> Option<&str> {
+        self.sync
+            .as_ref()
+            .and_then(|s| s.location_strategy.as_deref())
+    }
+
+    /// Configured merge handling name, if any
+    pub fn merges(&self) -> Option<&str> {
+        self.sync
+            .as_ref()
+            .and_then(|s| s.merges.as_deref())
+    }
+
+    /// Whether squash commit bodies are split into their original commits (default: false)
+    pub fn explode_squash(&self) -> bool {
+        self.sync
+            .as_ref()
+            .and_then(|s| s.explode_squash)
+            .unwrap_or(false)
+    }
+
+    /// Configured notes ref for SVCMS trailers, if any
+    pub fn notes_ref(&self) -> Option<&str> {
+        self.sync
+            .as_ref()
+            .and_then(|s| s.notes_ref.as_deref())
+    }
+
+    /// The `[locations]` rules, if any
+    pub fn locations(&self) -> Option<&std::collections::HashMap<String, String>> {
+        self.locations.as_ref()
+    }
+
+    /// The `[commit_types.scopes]` groups, if any
+    pub fn scopes(&self) -> Option<&CommitTypeScopesConfig> {
+        self.commit_types
+            .as_ref()
+            .and_then(|c| c.scopes.as_ref())
+    }
+
+    /// The `[cleanup]` section, if any
+    pub fn cleanup(&self) -> Option<&CleanupConfig> {
+        self.cleanup.as_ref()
+    }
+
+    /// Whether sync cleans up old memories afterwards (default: false)
+    pub fn auto_cleanup_on_sync(&self) -> bool {
+        self.cleanup
+            .as_ref()
+            .and_then(|c| c.auto_cleanup_on_sync)
+            .unwrap_or(false)
+    }
+
+    /// The `[cleanup.validation]` section, if any
+    pub fn validation(&self) -> Option<&ValidationConfig> {
+        self.cleanup
+            .as_ref()
+            .and_then(|c| c.validation.as_ref())
+    }
+
+    /// Whether `synaptic validate` exits non-zero on stale memories (default: false)
+    pub fn report_stale_memories(&self) -> bool {
+        self.validation()
+            .and_then(|v| v.report_stale_memories)
+            .unwrap_or(false)
+    }
+
+    /// The `[trust]` section, if any
+    pub fn trust(&self) -> Option<&TrustConfig> {
+        self.trust.as_ref()
+    }
+
+    /// Build a commit type validator from the `[commit_types]` section
+    pub fn commit_type_validator(&self) -> Option<CommitTypeValidator> {
+        self.commit_types.as_ref().map(CommitTypeValidator::from_config)
     }
 
     /// Save configuration to the default location
@@ -332,6 +546,14 @@ impl SynapticConfig {
                 default_depth: Some(100),
                 auto_deduplicate: Some(true),
                 dry_run: Some(false),
+                promote_breaking_changes: Some(false),
+                issue_url_template: None,
+                reverted_memories: Some("strike".to_string()),
+                changed_files: Some(false),
+                location_strategy: Some("scope".to_string()),
+                merges: Some("all".to_string()),
+                explode_squash: Some(false),
+                notes_ref: Some("refs/notes/svcms".to_string()),
             }),
             obsidian: Some(ObsidianConfig {
                 vault_path: Some("~/Documents/ObsidianVault".to_string()),
@@ -382,23 +604,29 @@ impl SynapticConfig {
                     categories
                 }),
                 scopes: Some(CommitTypeScopesConfig {
+                    cross_cutting_target: Some("CLAUDE.md".to_string()),
+                    tooling_target: Some("CLAUDE.md".to_string()),
                     modules: Some({
                         let mut modules = std::collections::HashMap::new();
                         modules.insert("auth".to_string(), ScopeConfig {
                             categories: vec!["standard", "knowledge", "collaboration"].into_iter().map(|s| s.to_string()).collect(),
                             custom_types: vec!["integrated".to_string()],
+                            path: None,
                         });
                         modules.insert("api".to_string(), ScopeConfig {
                             categories: vec!["standard", "knowledge"].into_iter().map(|s| s.to_string()).collect(),
                             custom_types: vec!["migrated".to_string()],
+                            path: None,
                         });
                         modules.insert("scheduler".to_string(), ScopeConfig {
                             categories: vec!["all".to_string()],
                             custom_types: vec![],
+                            path: None,
                         });
                         modules.insert("database".to_string(), ScopeConfig {
                             categories: vec!["standard", "knowledge"].into_iter().map(|s| s.to_string()).collect(),
                             custom_types: vec!["seeded".to_string()],
+                            path: None,
                         });
                         modules
                     }),
@@ -407,14 +635,17 @@ impl SynapticConfig {
                         cross_cutting.insert("architecture".to_string(), ScopeConfig {
                             categories: vec!["knowledge", "collaboration", "meta"].into_iter().map(|s| s.to_string()).collect(),
                             custom_types: vec![],
+                            path: None,
                         });
                         cross_cutting.insert("security".to_string(), ScopeConfig {
                             categories: vec!["knowledge", "collaboration"].into_iter().map(|s| s.to_string()).collect(),
                             custom_types: vec!["audited".to_string()],
+                            path: None,
                         });
                         cross_cutting.insert("performance".to_string(), ScopeConfig {
                             categories: vec!["standard", "knowledge"].into_iter().map(|s| s.to_string()).collect(),
                             custom_types: vec!["profiled".to_string()],
+                            path: None,
                         });
                         cross_cutting
                     }),
@@ -423,14 +654,17 @@ impl SynapticConfig {
                         tooling.insert("eslint".to_string(), ScopeConfig {
                             categories: vec!["standard", "meta"].into_iter().map(|s| s.to_string()).collect(),
                             custom_types: vec!["configured".to_string()],
+                            path: None,
                         });
                         tooling.insert("webpack".to_string(), ScopeConfig {
                             categories: vec!["standard", "knowledge"].into_iter().map(|s| s.to_string()).collect(),
                             custom_types: vec!["optimized".to_string()],
+                            path: None,
                         });
                         tooling.insert("docker".to_string(), ScopeConfig {
                             categories: vec!["standard", "knowledge"].into_iter().map(|s| s.to_string()).collect(),
                             custom_types: vec!["containerized".to_string()],
+                            path: None,
                         });
                         tooling
                     }),
@@ -439,10 +673,12 @@ impl SynapticConfig {
                         project_wide.insert("project".to_string(), ScopeConfig {
                             categories: vec!["all".to_string()],
                             custom_types: vec![],
+                            path: None,
                         });
                         project_wide.insert("global".to_string(), ScopeConfig {
                             categories: vec!["all".to_string()],
                             custom_types: vec![],
+                            path: None,
                         });
                         project_wide
                     }),
@@ -450,12 +686,14 @@ impl SynapticConfig {
             }),
             cleanup: Some(CleanupConfig {
                 mode: Some("archive".to_string()),
-                auto_cleanup_on_sync: Some(true),
+                auto_cleanup_on_sync: Some(false),
                 archive_format: Some("dated".to_string()),
                 retention_days: Some(30),
                 validation: Some(ValidationConfig {
                     check_frequency: Some("weekly".to_string()),
                     report_stale_memories: Some(true),
+                    stale_after_days: None,
+                    churn_threshold: None,
                 }),
             }),
             query: Some(QueryConfig {
@@ -468,6 +706,13 @@ impl SynapticConfig {
                 locations.insert("db".to_string(), "database/CLAUDE.md".to_string());
                 locations
             }),
+            trust: Some(TrustConfig {
+                require_signature: Some(false),
+                allowed_signers: Some("~/.ssh/allowed_signers".to_string()),
+                allowed_authors: Some(vec![]),
+                untrusted: Some("quarantine".to_string()),
+                quarantine_file: Some(".synaptic/quarantine.md".to_string()),
+            }),
         };
 
         sample_config.save_to(&config_path)?;
@@ -509,12 +754,15 @@ impl SynapticConfig {
                 aliases: None, // Global only
                 categories: None, // Global only - SVCMS standard categories
                 scopes: Some(CommitTypeScopesConfig {
+                    cross_cutting_target: None,
+                    tooling_target: None,
                     modules: Some({
                         let mut modules = std::collections::HashMap::new();
                         // Add some example module scopes based on common patterns
                         modules.insert("main".to_string(), ScopeConfig {
                             categories: vec!["standard".to_string(), "knowledge".to_string()],
                             custom_types: vec![],
+                            path: None,
                         });
                         modules
                     }),
@@ -530,6 +778,7 @@ impl SynapticConfig {
                 locations.insert("main".to_string(), "src/CLAUDE.md".to_string());
                 locations
             }),
+            trust: None, // Use global settings
         };
         
         project_config.save_to(&config_path)?;
@@ -550,6 +799,33 @@ impl SynapticConfig {
     }
 }
 
+impl Default for SynapticConfig {
+    /// Create default configuration
+    fn default() -> Self {
+        Self {
+            sync: Some(SyncConfig {
+                default_depth: Some(100),
+                auto_deduplicate: Some(true),
+                dry_run: Some(false),
+                promote_breaking_changes: Some(false),
+                issue_url_template: None,
+                reverted_memories: Some("strike".to_string()),
+                changed_files: Some(false),
+                location_strategy: Some("scope".to_string()),
+                merges: Some("all".to_string()),
+                explode_squash: Some(false),
+                notes_ref: Some("refs/notes/svcms".to_string()),
+            }),
+            obsidian: None,
+            commit_types: None,
+            cleanup: None,
+            query: None,
+            locations: None,
+            trust: None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -589,4 +865,47 @@ synaptic_folder = "synaptic"
         let loaded_config = SynapticConfig::load_from(&config_path).unwrap();
         assert!(loaded_config.sync.is_some());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_project_trust_cannot_loosen_global() {
+        let mut config: SynapticConfig = toml::from_str(r#"
+[trust]
+require_signature = true
+allowed_authors = ["*@acme.com"]
+"#).unwrap();
+        let project: SynapticConfig = toml::from_str(r#"
+[trust]
+require_signature = false
+allowed_authors = ["*@evil.test"]
+allowed_signers = "keys/allowed_signers"
+untrusted = "skip"
+quarantine_file = "CLAUDE.md"
+"#).unwrap();
+
+        config.merge(project);
+        let trust = config.trust().unwrap();
+        assert_eq!(trust.require_signature, Some(true));
+        assert_eq!(trust.allowed_authors, Some(vec!["*@acme.com".to_string()]));
+        assert_eq!(trust.allowed_signers, None);
+        assert_eq!(trust.quarantine_file, None);
+        assert_eq!(trust.untrusted.as_deref(), Some("skip"));
+    }
+
+    #[test]
+    fn test_broken_project_config_keeps_global_trust() {
+        let home = TempDir::new().unwrap();
+        let global_path = home.path().join("config.toml");
+        fs::write(&global_path, "[trust]\nallowed_authors = [\"*@acme.com\"]\n").unwrap();
+        
+        let project = TempDir::new().unwrap();
+        git2::Repository::init(project.path()).unwrap();
+        let config = SynapticConfig::load_layered(&global_path, project.path()).unwrap();
+        assert!(config.trust().is_some());
+        
+        // A broken project config is an error, not a config without `[trust]`
+        fs::create_dir_all(project.path().join(".synaptic")).unwrap();
+        fs::write(project.path().join(".synaptic").join("config.toml"), "this is [not toml").unwrap();
+        let error = SynapticConfig::load_layered(&global_path, project.path()).unwrap_err();
+        assert!(format!("{:#}", error).contains(".synaptic"));
+    }
+}
@@ -0,0 +1,226 @@
+//! Parse the dates accepted by `--since` and `--until`
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday};
+
+/// A time window commits are filtered by; either bound may be open
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DateRange {
+    pub since: Option<DateTime<FixedOffset>>,
+    pub until: Option<DateTime<FixedOffset>>,
+}
+
+impl DateRange {
+    /// Parse `--since`/`--until` values relative to the current local time
+    pub fn parse(since: Option<&str>, until: Option<&str>) -> Result<Self> {
+        let now = Local::now();
+        Ok(DateRange {
+            since: since.map(|date| parse_date(date, now)).transpose()?,
+            until: until.map(|date| parse_end_date(date, now)).transpose()?,
+        })
+    }
+    
+    /// Check if a commit time falls inside the range (bounds inclusive)
+    pub fn contains(&self, time: &DateTime<FixedOffset>) -> bool {
+        self.since.is_none_or(|since| *time >= since) && self.until.is_none_or(|until| *time <= until)
+    }
+}
+
+/// Parse an absolute or relative date, in the timezone of `now`
+///
+/// Accepts `YYYY-MM-DD` (midnight), `YYYY-MM-DD HH:MM[:SS]`, RFC 3339,
+/// `now`, `today`, `yesterday`, `<n> <unit>s ago` and `last <weekday>`.
+/// Wall-clock times get the timezone's offset on that date, so a date across
+/// a DST change from `now` still means its own local midnight.
+pub fn parse_date<Tz: TimeZone>(input: &str, now: DateTime<Tz>) -> Result<DateTime<FixedOffset>> {
+    parse_bound(input, now, false)
+}
+
+/// Parse a date like `parse_date`, as the end of a range: whole days
+/// (`YYYY-MM-DD`, `yesterday`, `last friday`, ...) mean their last second
+pub fn parse_end_date<Tz: TimeZone>(input: &str, now: DateTime<Tz>) -> Result<DateTime<FixedOffset>> {
+    parse_bound(input, now, true)
+}
+
+fn parse_bound<Tz: TimeZone>(input: &str, now: DateTime<Tz>, end_of_day: bool) -> Result<DateTime<FixedOffset>> {
+    let input = input.trim();
+    let lower = input.to_lowercase();
+    let timezone = now.timezone();
+    // Commit times have whole seconds, so a day ends a second before the next one
+    let midnight = |date: NaiveDate| match end_of_day {
+        false => local_time(&timezone, date.and_time(NaiveTime::MIN)),
+        true => local_time(&timezone, (date + Duration::days(1)).and_time(NaiveTime::MIN))
+            .map(|next| next - Duration::seconds(1)),
+    };
+    
+    if let Ok(time) = DateTime::parse_from_rfc3339(input) {
+        return Ok(time);
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(input, format) {
+            return local_time(&timezone, time);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return midnight(date);
+    }
+    
+    match lower.as_str() {
+        "now" => return Ok(now.fixed_offset()),
+        "today" => return midnight(now.date_naive()),
+        "yesterday" => return midnight(now.date_naive() - Duration::days(1)),
+        _ => {}
+    }
+    
+    let words: Vec<&str> = lower.split_whitespace().collect();
+    match words.as_slice() {
+        [amount, unit, "ago"] => {
+            let amount: u32 = amount.parse()
+                .map_err(|_| anyhow!("Invalid date: {}", input))?;
+            subtract(now, amount, unit.trim_end_matches('s'))
+                .map(|time| time.fixed_offset())
+                .ok_or_else(|| anyhow!("Invalid date: {}", input))
+        }
+        ["last", day] => {
+            let weekday: Weekday = day.parse()
+                .map_err(|_| anyhow!("Invalid date: {}", input))?;
+            // The most recent such day before today
+            let mut date = now.date_naive() - Duration::days(1);
+            while date.weekday() != weekday {
+                date -= Duration::days(1);
+            }
+            midnight(date)
+        }
+        _ => Err(anyhow!(
+            "Invalid date: {} (use YYYY-MM-DD, \"2 weeks ago\" or \"last monday\")",
+            input
+        )),
+    }
+}
+
+/// Go back `amount` units from `now`
+fn subtract<Tz: TimeZone>(now: DateTime<Tz>, amount: u32, unit: &str) -> Option<DateTime<Tz>> {
+    let amount_i64 = i64::from(amount);
+    match unit {
+        "second" => Some(now - Duration::seconds(amount_i64)),
+        "minute" => Some(now - Duration::minutes(amount_i64)),
+        "hour" => Some(now - Duration::hours(amount_i64)),
+        "day" => Some(now - Duration::days(amount_i64)),
+        "week" => Some(now - Duration::weeks(amount_i64)),
+        "month" => now.checked_sub_months(Months::new(amount)),
+        "year" => now.checked_sub_months(Months::new(amount.checked_mul(12)?)),
+        _ => None,
+    }
+}
+
+/// Interpret a wall-clock time in a timezone, taking the earlier of a repeated hour
+fn local_time<Tz: TimeZone>(timezone: &Tz, time: NaiveDateTime) -> Result<DateTime<FixedOffset>> {
+    timezone.from_local_datetime(&time)
+        .earliest()
+        .map(|time| time.fixed_offset())
+        .ok_or_else(|| anyhow!("Invalid local time: {}", time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn now() -> DateTime<FixedOffset> {
+        // A Thursday, in UTC+2
+        DateTime::parse_from_rfc3339("2026-10-15T14:30:00+02:00").unwrap()
+    }
+    
+    fn date(value: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(value).unwrap()
+    }
+    
+    #[test]
+    fn test_parse_absolute_dates() {
+        // Midnight in the local timezone, not UTC
+        assert_eq!(parse_date("2026-10-01", now()).unwrap(), date("2026-10-01T00:00:00+02:00"));
+        assert_eq!(parse_date("2026-10-01 09:15", now()).unwrap(), date("2026-10-01T09:15:00+02:00"));
+        assert_eq!(parse_date("2026-10-01T09:15:00-05:00", now()).unwrap(), date("2026-10-01T09:15:00-05:00"));
+    }
+    
+    #[test]
+    fn test_parse_relative_dates() {
+        assert_eq!(parse_date("2 weeks ago", now()).unwrap(), date("2026-10-01T14:30:00+02:00"));
+        assert_eq!(parse_date("1 month ago", now()).unwrap(), date("2026-09-15T14:30:00+02:00"));
+        assert_eq!(parse_date("3 hours ago", now()).unwrap(), date("2026-10-15T11:30:00+02:00"));
+        assert_eq!(parse_date("yesterday", now()).unwrap(), date("2026-10-14T00:00:00+02:00"));
+        assert_eq!(parse_date("last monday", now()).unwrap(), date("2026-10-12T00:00:00+02:00"));
+        assert_eq!(parse_date("Last Thursday", now()).unwrap(), date("2026-10-08T00:00:00+02:00"));
+        assert!(parse_date("next tuesday", now()).is_err());
+        assert!(parse_date("2 fortnights ago", now()).is_err());
+    }
+    
+    /// UTC+2 until 2026-10-25 01:00 UTC, UTC+1 after, like Central European time
+    #[derive(Debug, Clone, Copy)]
+    struct Cet;
+    
+    impl TimeZone for Cet {
+        type Offset = FixedOffset;
+        
+        fn from_offset(_: &FixedOffset) -> Self {
+            Cet
+        }
+        
+        fn offset_from_local_date(&self, local: &NaiveDate) -> chrono::MappedLocalTime<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_time(NaiveTime::MIN))
+        }
+        
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> chrono::MappedLocalTime<FixedOffset> {
+            let change = NaiveDate::from_ymd_opt(2026, 10, 25).unwrap().and_hms_opt(2, 0, 0).unwrap();
+            let hours = if *local < change { 2 } else { 1 };
+            chrono::MappedLocalTime::Single(FixedOffset::east_opt(hours * 3600).unwrap())
+        }
+        
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_time(NaiveTime::MIN))
+        }
+        
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            let change = NaiveDate::from_ymd_opt(2026, 10, 25).unwrap().and_hms_opt(1, 0, 0).unwrap();
+            FixedOffset::east_opt(if *utc < change { 2 } else { 1 } * 3600).unwrap()
+        }
+    }
+    
+    #[test]
+    fn test_parse_dates_across_dst() {
+        let now = Cet.from_utc_datetime(&date("2026-10-26T12:00:00Z").naive_utc());
+        // Before the change, midnight is at UTC+2, not the current UTC+1
+        assert_eq!(parse_date("2026-10-01", now).unwrap(), date("2026-10-01T00:00:00+02:00"));
+        assert_eq!(parse_date("2026-10-01 09:15", now).unwrap(), date("2026-10-01T09:15:00+02:00"));
+        assert_eq!(parse_date("2026-11-01", now).unwrap(), date("2026-11-01T00:00:00+01:00"));
+        assert_eq!(parse_date("last friday", now).unwrap(), date("2026-10-23T00:00:00+02:00"));
+    }
+    
+    #[test]
+    fn test_date_range_contains() {
+        let range = DateRange {
+            since: Some(date("2026-10-01T00:00:00+02:00")),
+            until: Some(date("2026-10-02T00:00:00+02:00")),
+        };
+        // Same instant expressed in another timezone
+        assert!(range.contains(&date("2026-09-30T22:00:00+00:00")));
+        assert!(!range.contains(&date("2026-09-30T21:59:59+00:00")));
+        assert!(!range.contains(&date("2026-10-02T00:00:01+02:00")));
+        assert!(DateRange::default().contains(&date("1999-01-01T00:00:00+00:00")));
+    }
+    
+    #[test]
+    fn test_until_date_includes_the_whole_day() {
+        let range = DateRange {
+            since: Some(parse_date("2026-10-01", now()).unwrap()),
+            until: Some(parse_end_date("2026-10-01", now()).unwrap()),
+        };
+        assert!(range.contains(&date("2026-10-01T00:00:00+02:00")));
+        assert!(range.contains(&date("2026-10-01T23:59:59+02:00")));
+        assert!(!range.contains(&date("2026-10-02T00:00:00+02:00")));
+        
+        // Times and relative dates are exact
+        assert_eq!(parse_end_date("2026-10-01 09:15", now()).unwrap(), date("2026-10-01T09:15:00+02:00"));
+        assert_eq!(parse_end_date("3 hours ago", now()).unwrap(), date("2026-10-15T11:30:00+02:00"));
+        assert_eq!(parse_end_date("yesterday", now()).unwrap(), date("2026-10-14T23:59:59+02:00"));
+    }
+}
@@ -0,0 +1,279 @@
+//! Structured diagnostics for SVCMS commit messages
+
+use std::fmt;
+use std::path::{Component, Path};
+use crate::commit_types::TypeRejection;
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The commit is not treated as an SVCMS commit
+    Error,
+    /// The commit is parsed, but something in it is likely a mistake
+    Warning,
+}
+
+/// Position in the commit message: 1-based line and column, length in characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub length: usize,
+}
+
+impl Span {
+    pub fn new(line: usize, column: usize, length: usize) -> Self {
+        Span { line, column, length }
+    }
+}
+
+/// What a diagnostic is about
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiagnosticKind {
+    /// The message has no content
+    EmptyMessage,
+    /// The header doesn't follow `[<category>.]<type>(<scope>): <summary>`
+    MalformedHeader,
+    /// The type is not known to the validator
+    UnknownType { commit_type: String, suggestion: Option<String> },
+    /// The category of a two-tier header doesn't exist
+    UnknownCategory { category: String, suggestion: Option<String> },
+    /// The category exists but doesn't contain the type
+    TypeNotInCategory { category: String, commit_type: String, suggestion: Option<String> },
+    /// The scope is configured and doesn't allow the category
+    CategoryNotAllowed { scope: String, category: String },
+    /// A `Memory:` footer has no content
+    EmptyMemory,
+    /// A `Location:` footer points outside the repository
+    LocationOutsideRepository { location: String },
+    /// The summary is longer than the spec's 72 characters
+    SummaryTooLong { length: usize },
+    /// A memory is too long to be a concise insight
+    MemoryTooLong { length: usize },
+    /// A memory only repeats the header summary
+    MemoryRepeatsSummary,
+    /// A knowledge commit has no `Memory:` footer, so nothing gets synced
+    MissingMemory { commit_type: String },
+}
+
+impl DiagnosticKind {
+    /// Severity of this kind of diagnostic
+    pub fn severity(&self) -> Severity {
+        match self {
+            DiagnosticKind::EmptyMemory |
+            DiagnosticKind::LocationOutsideRepository { .. } |
+            DiagnosticKind::SummaryTooLong { .. } |
+            DiagnosticKind::MemoryTooLong { .. } |
+            DiagnosticKind::MemoryRepeatsSummary |
+            DiagnosticKind::MissingMemory { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    /// Build the diagnostic for a validator rejection
+    pub fn from_rejection(rejection: TypeRejection, known_types: &[String], categories: &[String]) -> Self {
+        match rejection {
+            TypeRejection::UnknownType { commit_type } => {
+                let suggestion = closest_match(&commit_type, known_types.iter().map(|t| t.as_str()));
+                DiagnosticKind::UnknownType { commit_type, suggestion }
+            }
+            TypeRejection::UnknownCategory { category } => {
+                let suggestion = closest_match(&category, categories.iter().map(|c| c.as_str()));
+                DiagnosticKind::UnknownCategory { category, suggestion }
+            }
+            TypeRejection::TypeNotInCategory { category, commit_type } => {
+                DiagnosticKind::TypeNotInCategory { category, commit_type, suggestion: None }
+            }
+            TypeRejection::CategoryNotAllowed { scope, category } => {
+                DiagnosticKind::CategoryNotAllowed { scope, category }
+            }
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticKind::EmptyMessage => write!(f, "commit message is empty"),
+            DiagnosticKind::MalformedHeader => write!(
+                f,
+                "header does not match `[<category>.]<type>(<scope>): <summary>`"
+            ),
+            DiagnosticKind::UnknownType { commit_type, suggestion } => {
+                write!(f, "unknown type `{}`", commit_type)?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, ", did you mean `{}`", suggestion)?;
+                }
+                Ok(())
+            }
+            DiagnosticKind::UnknownCategory { category, suggestion } => {
+                write!(f, "unknown category `{}`", category)?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, ", did you mean `{}`", suggestion)?;
+                }
+                Ok(())
+            }
+            DiagnosticKind::TypeNotInCategory { category, commit_type, suggestion } => {
+                write!(f, "category `{}` has no type `{}`", category, commit_type)?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, ", did you mean `{}`", suggestion)?;
+                }
+                Ok(())
+            }
+            DiagnosticKind::CategoryNotAllowed { scope, category } => {
+                write!(f, "scope `{}` does not allow category `{}`", scope, category)
+            }
+            DiagnosticKind::EmptyMemory => write!(f, "Memory footer is empty"),
+            DiagnosticKind::LocationOutsideRepository { location } => {
+                write!(f, "Location `{}` is outside the repository", location)
+            }
+            DiagnosticKind::SummaryTooLong { length } => {
+                write!(f, "summary is {} characters, keep it under 72", length)
+            }
+            DiagnosticKind::MemoryTooLong { length } => {
+                write!(f, "Memory is {} characters, keep it a concise, actionable insight", length)
+            }
+            DiagnosticKind::MemoryRepeatsSummary => {
+                write!(f, "Memory repeats the summary instead of stating the insight")
+            }
+            DiagnosticKind::MissingMemory { commit_type } => {
+                write!(f, "`{}` commit has no Memory footer, nothing will be synced", commit_type)
+            }
+        }
+    }
+}
+
+/// A single problem found while parsing a commit message
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+    pub span: Span,
+    /// Extra guidance, e.g. the valid alternatives
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic with the kind's default severity
+    pub fn new(kind: DiagnosticKind, span: Span) -> Self {
+        Diagnostic {
+            severity: kind.severity(),
+            kind,
+            span,
+            help: None,
+        }
+    }
+
+    /// Attach a help message
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}:{}: {}: {}", self.span.line, self.span.column, label, self.kind)
+    }
+}
+
+/// Check if a `Location:` value would resolve outside the repository
+///
+/// `~/` paths are allowed, since the spec uses them for user-global memories.
+pub fn location_escapes_repository(location: &str) -> bool {
+    if location.starts_with("~/") {
+        return false;
+    }
+
+    let path = Path::new(location);
+    let mut depth: i32 = 0;
+
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => return true,
+            Component::ParentDir => depth -= 1,
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+        }
+        if depth < 0 {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Find the candidate closest to `input`, if it is close enough to be a typo
+pub fn closest_match<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    // Allow roughly one edit per three characters, at least one
+    let max_distance = (input.chars().count() / 3).max(1);
+
+    candidates.into_iter()
+        .filter(|candidate| *candidate != input)
+        .map(|candidate| (edit_distance(input, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b_chars.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_match() {
+        let candidates = ["learned", "insight", "feat", "fix"];
+        assert_eq!(closest_match("learnd", candidates), Some("learned".to_string()));
+        assert_eq!(closest_match("lerned", candidates), Some("learned".to_string()));
+        assert_eq!(closest_match("fx", candidates), Some("fix".to_string()));
+        assert_eq!(closest_match("banana", candidates), None);
+    }
+
+    #[test]
+    fn test_location_escapes_repository() {
+        assert!(!location_escapes_repository("src/api/CLAUDE.md"));
+        assert!(!location_escapes_repository("./CLAUDE.md"));
+        assert!(!location_escapes_repository("docs/../CLAUDE.md"));
+        assert!(!location_escapes_repository("~/.claude/CLAUDE.md"));
+        assert!(location_escapes_repository("../other/CLAUDE.md"));
+        assert!(location_escapes_repository("/etc/CLAUDE.md"));
+    }
+
+    #[test]
+    fn test_diagnostic_display() {
+        let diagnostic = Diagnostic::new(
+            DiagnosticKind::UnknownType {
+                commit_type: "learnd".to_string(),
+                suggestion: Some("learned".to_string()),
+            },
+            Span::new(1, 1, 6),
+        );
+        assert!(diagnostic.is_error());
+        assert_eq!(diagnostic.to_string(), "1:1: error: unknown type `learnd`, did you mean `learned`");
+    }
+}
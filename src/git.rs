@@ -1,93 +1,800 @@
 //! Git repository interaction
 
-use git2::Repository;
+use std::collections::{HashMap, HashSet};
+use git2::{Delta, DiffFindOptions, Mailmap, Oid, Patch, Repository, RevparseMode, Revwalk, Signature, Sort};
 use anyhow::{Result, Context};
-use chrono::{Utc, TimeZone};
-use crate::SvcmsCommit;
-use crate::parser::parse_commit_message;
+use chrono::{DateTime, FixedOffset, TimeZone};
+use crate::{ChangeStatus, FileChange, Person, SvcmsCommit};
+use crate::commit_types::CommitTypeValidator;
+use crate::config::SynapticConfig;
+use crate::dates::DateRange;
+use crate::parser::{merge_note, note_has_header, parse_commit_message_with_validator, reverted_commit, split_squash_message};
 
-/// Get SVCMS commits from the repository
-pub fn get_svcms_commits(repo_path: &str, depth: usize) -> Result<Vec<SvcmsCommit>> {
-    let repo = Repository::open(repo_path)
-        .context("Failed to open Git repository")?;
+/// Notes ref read for SVCMS trailers added after the fact
+pub const DEFAULT_NOTES_REF: &str = "refs/notes/svcms";
+
+/// Build the commit type validator from the merged config for a repository
+///
+/// A config that fails to parse is an error, not a fallback to the builtin types.
+fn load_validator(repo_path: &str) -> Result<Option<CommitTypeValidator>> {
+    let config = SynapticConfig::load_for_repo(std::path::Path::new(repo_path))?;
+    Ok(config.commit_type_validator())
+}
+
+/// Commit time in the committer's timezone
+fn commit_time(commit: &git2::Commit) -> DateTime<FixedOffset> {
+    let time = commit.time();
+    let offset = FixedOffset::east_opt(time.offset_minutes() * 60)
+        .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+    offset.timestamp_opt(time.seconds(), 0)
+        .single()
+        .unwrap_or_else(|| chrono::Utc::now().fixed_offset())
+}
+
+/// Person from a git signature
+fn signature_person(signature: &Signature) -> Person {
+    let email = signature.email().filter(|email| !email.is_empty()).map(str::to_string);
+    Person::new(String::from_utf8_lossy(signature.name_bytes()), email)
+}
+
+/// Resolve a trailer person through `.mailmap`, when they have an email to match on
+fn resolve_person(person: Person, mailmap: &Mailmap) -> Person {
+    let resolved = person.email.as_deref()
+        .and_then(|email| Signature::now(&person.name, email).ok())
+        .and_then(|signature| mailmap.resolve_signature(&signature).ok());
     
-    let mut revwalk = repo.revwalk()?;
-    revwalk.push_head()?;
+    match resolved {
+        Some(signature) => signature_person(&signature),
+        None => person,
+    }
+}
+
+/// Parse a message of a git commit into an SVCMS commit, if it follows the
+/// format, taking SHA, time and people from the commit
+///
+/// Author, committer and co-authors are resolved through `.mailmap` when given.
+fn parse_git_message(
+    commit: &git2::Commit,
+    message: &str,
+    validator: Option<&CommitTypeValidator>,
+    mailmap: Option<&Mailmap>
+) -> Result<Option<SvcmsCommit>> {
+    let timestamp = commit_time(commit);
     
-    let mut commits = Vec::new();
-    let mut count = 0;
+    let Some(mut svcms_commit) = parse_commit_message_with_validator(&short_sha(commit.id()), message, timestamp, validator)? else {
+        return Ok(None);
+    };
+    svcms_commit.full_sha = Some(commit.id().to_string());
     
-    for oid in revwalk {
-        if count >= depth {
-            break;
+    match mailmap {
+        Some(mailmap) => {
+            svcms_commit.author = commit.author_with_mailmap(mailmap).ok().map(|s| signature_person(&s));
+            svcms_commit.committer = commit.committer_with_mailmap(mailmap).ok().map(|s| signature_person(&s));
+            svcms_commit.co_authors = svcms_commit.co_authors.into_iter()
+                .map(|person| resolve_person(person, mailmap))
+                .collect();
+        }
+        None => {
+            svcms_commit.author = Some(signature_person(&commit.author()));
+            svcms_commit.committer = Some(signature_person(&commit.committer()));
+        }
+    }
+    
+    Ok(Some(svcms_commit))
+}
+
+/// How merge and squash-merge commits are read
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MergePolicy {
+    /// Follow only the first parent of merges, like `git log --first-parent`
+    pub first_parent: bool,
+    /// Skip merge commits, like `git log --no-merges`
+    pub ignore_merges: bool,
+    /// Read each `* type(scope): ...` bullet of a squash or merge commit body
+    /// as its own commit, sharing the squash commit's SHA
+    pub explode_squash: bool,
+}
+
+impl MergePolicy {
+    /// Build the policy from the `[sync] merges` and `explode_squash` settings
+    pub fn from_config(config: &SynapticConfig) -> Self {
+        MergePolicy {
+            first_parent: config.merges() == Some("first-parent"),
+            ignore_merges: config.merges() == Some("ignore"),
+            explode_squash: config.explode_squash(),
+        }
+    }
+}
+
+/// Which commits to walk, in git revision syntax
+///
+/// Each spec is a revision (`main`, `v1.2.0`), a range (`v1.2.0..HEAD`), a
+/// symmetric difference (`origin/main...feature`) or an exclusion (`^main`).
+/// Without specs the walk starts at HEAD, unless `all` is set.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Revisions {
+    pub specs: Vec<String>,
+    /// Walk every ref, like `git log --all`
+    pub all: bool,
+    pub merges: MergePolicy,
+    /// Notes ref whose SVCMS trailers are merged into each commit
+    pub notes: Option<String>,
+}
+
+impl Revisions {
+    /// Walk from HEAD
+    pub fn head() -> Self {
+        Self::default()
+    }
+    
+    pub fn new(specs: Vec<String>, all: bool) -> Self {
+        Revisions { specs, all, merges: MergePolicy::default(), notes: None }
+    }
+    
+    /// Read merges according to a policy
+    pub fn with_merge_policy(mut self, merges: MergePolicy) -> Self {
+        self.merges = merges;
+        self
+    }
+    
+    /// Merge SVCMS trailers from a notes ref into the commits
+    pub fn with_notes(mut self, notes_ref: Option<String>) -> Self {
+        self.notes = notes_ref;
+        self
+    }
+    
+    /// Push the revisions onto a walk
+    fn push_onto(&self, repo: &Repository, revwalk: &mut Revwalk) -> Result<()> {
+        if self.merges.first_parent {
+            revwalk.simplify_first_parent()?;
         }
         
-        let oid = oid?;
-        let commit = repo.find_commit(oid)?;
+        if self.all {
+            revwalk.push_glob("*")?;
+            // Like git, --all includes a detached HEAD
+            if repo.head().is_ok() {
+                revwalk.push_head()?;
+            }
+        } else if self.specs.is_empty() {
+            revwalk.push_head()?;
+        }
         
-        // Parse commit message
-        if let Some(message) = commit.message() {
-            // Convert git2 time to chrono DateTime
-            let timestamp = Utc.timestamp_opt(commit.time().seconds(), 0)
-                .single()
-                .unwrap_or_else(Utc::now);
+        for spec in &self.specs {
+            if let Some(excluded) = spec.strip_prefix('^') {
+                let commit = repo.revparse_single(excluded)
+                    .with_context(|| format!("Unknown revision: {}", excluded))?
+                    .peel_to_commit()?;
+                revwalk.hide(commit.id())?;
+                continue;
+            }
             
-            // Use short SHA (first 7 chars) like git log
-            let sha = oid.to_string()[..7].to_string();
+            let revspec = repo.revparse(spec)
+                .with_context(|| format!("Invalid revision: {}", spec))?;
+            let from = revspec.from().map(|object| object.peel_to_commit()).transpose()?;
+            let to = revspec.to().map(|object| object.peel_to_commit()).transpose()?;
             
-            if let Some(svcms_commit) = parse_commit_message(&sha, message, timestamp)? {
-                commits.push(svcms_commit);
+            match (from, to) {
+                (Some(from), None) => revwalk.push(from.id())?,
+                (Some(from), Some(to)) if revspec.mode().contains(RevparseMode::MERGE_BASE) => {
+                    revwalk.push(from.id())?;
+                    revwalk.push(to.id())?;
+                    if let Ok(base) = repo.merge_base(from.id(), to.id()) {
+                        revwalk.hide(base)?;
+                    }
+                }
+                (Some(from), Some(to)) => {
+                    revwalk.push(to.id())?;
+                    revwalk.hide(from.id())?;
+                }
+                (None, _) => return Err(anyhow::anyhow!("Invalid revision: {}", spec)),
             }
         }
         
-        count += 1;
+        Ok(())
+    }
+}
+
+/// Short SHA (first 7 chars) like git log
+fn short_sha(oid: Oid) -> String {
+    oid.to_string()[..7].to_string()
+}
+
+/// The git commit an SVCMS commit was read from
+///
+/// Uses the full SHA when the commit has one, since a short SHA can be
+/// ambiguous in a large repository.
+pub fn find_commit<'r>(repo: &'r Repository, svcms_commit: &SvcmsCommit) -> Result<git2::Commit<'r>> {
+    let commit = match &svcms_commit.full_sha {
+        Some(full_sha) => Oid::from_str(full_sha).and_then(|oid| repo.find_commit(oid)),
+        None => repo.revparse_single(&svcms_commit.sha).and_then(|object| object.peel_to_commit()),
+    };
+    commit.with_context(|| format!("Failed to find commit {}", svcms_commit.sha))
+}
+
+/// Collects SVCMS commits from a revision walk, linking reverts to the
+/// commits they undo
+struct CommitCollector<'a> {
+    repo: &'a Repository,
+    validator: Option<&'a CommitTypeValidator>,
+    mailmap: Option<Mailmap>,
+    merges: MergePolicy,
+    notes: Option<String>,
+    commits: Vec<SvcmsCommit>,
+    /// Reverted commit → short SHA of the commit reverting it
+    reverts: HashMap<Oid, String>,
+}
+
+impl<'a> CommitCollector<'a> {
+    fn new(repo: &'a Repository, validator: Option<&'a CommitTypeValidator>, revisions: &Revisions) -> Self {
+        CommitCollector {
+            repo,
+            validator,
+            mailmap: repo.mailmap().ok(),
+            merges: revisions.merges,
+            notes: revisions.notes.clone(),
+            commits: Vec::new(),
+            reverts: HashMap::new(),
+        }
     }
     
-    Ok(commits)
+    /// Add the next commit of a newest-first walk
+    fn add(&mut self, commit: &git2::Commit) -> Result<()> {
+        if self.merges.ignore_merges && commit.parent_count() > 1 {
+            return Ok(());
+        }
+        
+        let reverted_by = self.reverts.remove(&commit.id());
+        
+        // A revert that was itself reverted doesn't retract anything
+        if reverted_by.is_none() {
+            self.record_revert(commit);
+        }
+        
+        for mut svcms_commit in self.parse(commit)? {
+            svcms_commit.reverted_by = reverted_by.clone();
+            self.commits.push(svcms_commit);
+        }
+        
+        Ok(())
+    }
+    
+    /// Parse a commit with its note, exploding squash bodies when the policy
+    /// asks for it
+    ///
+    /// A note's trailers go into the last commit read from the message, so its
+    /// memories are numbered after the message's own. When the message isn't
+    /// SVCMS, a note with its own header stands in for it.
+    fn parse(&self, commit: &git2::Commit) -> Result<Vec<SvcmsCommit>> {
+        let mut commits = match commit.message() {
+            Some(message) => self.parse_message(commit, message)?,
+            None => Vec::new(),
+        };
+        
+        let note = self.notes.as_deref()
+            .and_then(|notes_ref| self.repo.find_note(Some(notes_ref), commit.id()).ok())
+            .and_then(|note| note.message().map(str::to_string));
+        if let Some(note) = note {
+            match commits.last_mut() {
+                Some(last) => merge_note(last, &note)?,
+                None if note_has_header(&note) => {
+                    commits.extend(parse_git_message(commit, &note, self.validator, self.mailmap.as_ref())?);
+                }
+                None => {}
+            }
+        }
+        
+        Ok(commits)
+    }
+    
+    /// Parse a commit message, exploding squash bodies when the policy asks for it
+    fn parse_message(&self, commit: &git2::Commit, message: &str) -> Result<Vec<SvcmsCommit>> {
+        if self.merges.explode_squash {
+            if let Some(messages) = split_squash_message(message) {
+                let mut commits: Vec<SvcmsCommit> = Vec::new();
+                let mut offset = 0;
+                for message in messages {
+                    if let Some(mut piece) = parse_git_message(commit, &message, self.validator, self.mailmap.as_ref())? {
+                        piece.memory_offset = offset;
+                        offset += piece.memory.len();
+                        commits.push(piece);
+                    }
+                }
+                if !commits.is_empty() {
+                    return Ok(commits);
+                }
+            }
+        }
+        
+        Ok(parse_git_message(commit, message, self.validator, self.mailmap.as_ref())?.into_iter().collect())
+    }
+    
+    /// Remember which commit this one reverts, if it is a revert
+    fn record_revert(&mut self, commit: &git2::Commit) {
+        let target = commit.message()
+            .and_then(reverted_commit)
+            .and_then(|sha| self.repo.revparse_single(&sha).ok());
+        
+        if let Some(target) = target {
+            self.reverts.insert(target.id(), short_sha(commit.id()));
+        }
+    }
+    
+    /// Finish the walk, pulling in reverted commits older than the walked window
+    /// so their memories can still be retracted
+    fn finish(mut self) -> Result<Vec<SvcmsCommit>> {
+        let mut outside_window = Vec::new();
+        
+        for (oid, reverted_by) in std::mem::take(&mut self.reverts) {
+            let Ok(commit) = self.repo.find_commit(oid) else { continue };
+            for mut svcms_commit in self.parse(&commit)? {
+                svcms_commit.reverted_by = Some(reverted_by.clone());
+                outside_window.push(svcms_commit);
+            }
+        }
+        
+        outside_window.sort_by_key(|commit| std::cmp::Reverse(commit.timestamp));
+        self.commits.extend(outside_window);
+        
+        Ok(self.commits)
+    }
 }
 
-/// Get SVCMS commits since a specific date
-pub fn get_svcms_commits_since(repo_path: &str, since_date: &str) -> Result<Vec<SvcmsCommit>> {
+/// Get SVCMS commits from the repository
+pub fn get_svcms_commits(repo_path: &str, revisions: &Revisions, depth: usize) -> Result<Vec<SvcmsCommit>> {
+    let validator = load_validator(repo_path)?;
+    get_svcms_commits_with_validator(repo_path, revisions, depth, validator.as_ref())
+}
+
+/// Get SVCMS commits from the repository using a specific validator
+pub fn get_svcms_commits_with_validator(
+    repo_path: &str,
+    revisions: &Revisions,
+    depth: usize,
+    validator: Option<&CommitTypeValidator>
+) -> Result<Vec<SvcmsCommit>> {
     let repo = Repository::open(repo_path)
         .context("Failed to open Git repository")?;
     
-    // Parse the since date
-    let since_timestamp = chrono::NaiveDate::parse_from_str(since_date, "%Y-%m-%d")
-        .context("Invalid date format. Use YYYY-MM-DD")?
-        .and_hms_opt(0, 0, 0)
-        .unwrap()
-        .and_utc()
-        .timestamp();
+    let mut revwalk = repo.revwalk()?;
+    revisions.push_onto(&repo, &mut revwalk)?;
+    
+    let mut collector = CommitCollector::new(&repo, validator, revisions);
+    
+    for oid in revwalk.take(depth) {
+        let oid = oid?;
+        let commit = repo.find_commit(oid)?;
+        collector.add(&commit)?;
+    }
+    
+    collector.finish()
+}
+
+/// Get SVCMS commits committed within a date range
+pub fn get_svcms_commits_between(repo_path: &str, revisions: &Revisions, dates: &DateRange) -> Result<Vec<SvcmsCommit>> {
+    let validator = load_validator(repo_path)?;
+    get_svcms_commits_between_with_validator(repo_path, revisions, dates, validator.as_ref())
+}
+
+/// Get SVCMS commits committed within a date range using a specific validator
+///
+/// Walks in commit time order and filters every commit rather than stopping
+/// at the first old one, so merged branches and skewed clocks don't hide
+/// commits in the range.
+pub fn get_svcms_commits_between_with_validator(
+    repo_path: &str,
+    revisions: &Revisions,
+    dates: &DateRange,
+    validator: Option<&CommitTypeValidator>
+) -> Result<Vec<SvcmsCommit>> {
+    let repo = Repository::open(repo_path)
+        .context("Failed to open Git repository")?;
     
     let mut revwalk = repo.revwalk()?;
-    revwalk.push_head()?;
+    revwalk.set_sorting(Sort::TIME)?;
+    revisions.push_onto(&repo, &mut revwalk)?;
     
-    let mut commits = Vec::new();
+    let mut collector = CommitCollector::new(&repo, validator, revisions);
     
     for oid in revwalk {
         let oid = oid?;
         let commit = repo.find_commit(oid)?;
         
-        // Stop if we've gone past the since date
-        if commit.time().seconds() < since_timestamp {
-            break;
+        if dates.contains(&commit_time(&commit)) {
+            collector.add(&commit)?;
         }
+    }
+    
+    collector.finish()
+}
+
+/// What makes two commits copies of the same change
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ChangeIdentity {
+    /// The commit itself, or the source named by a cherry-pick line
+    Commit(Oid),
+    /// Patch-id of the diff against the first parent
+    Patch(Oid),
+    /// Normalized header and memories
+    Message(String),
+}
+
+/// Merge copies of the same change, such as cherry-picks and rebased commits
+///
+/// Commits are copies when one names the other in a `(cherry picked from
+/// commit …)` line, when their diffs have the same patch-id, or when they
+/// carry the same memories under the same normalized header. Patch-ids are
+/// only computed for commits sharing a normalized header with another, since
+/// each needs a diff. The same commit read twice (from the walk and from its
+/// note) is kept once, with the most memories, and not counted. Each group keeps
+/// its oldest commit (ties broken by SHA), which lists the others in
+/// `duplicates`. The group counts as reverted only when every copy is.
+/// Returns how many commits were merged away.
+pub fn deduplicate_commits(repo_path: &str, commits: &mut Vec<SvcmsCommit>) -> Result<usize> {
+    let repo = Repository::open(repo_path)
+        .context("Failed to open Git repository")?;
+    
+    let mut oids = Vec::with_capacity(commits.len());
+    for svcms_commit in commits.iter() {
+        oids.push(find_commit(&repo, svcms_commit)?.id());
+    }
+    
+    // Drop repeats of a commit (or squash piece) before looking for copies
+    let mut read: HashMap<(Oid, usize, &str), usize> = HashMap::new();
+    let mut repeated = vec![false; commits.len()];
+    for (i, svcms_commit) in commits.iter().enumerate() {
+        let key = (oids[i], svcms_commit.memory_offset, svcms_commit.summary.as_str());
+        match read.get(&key) {
+            Some(&kept) if svcms_commit.memory.len() > commits[kept].memory.len() => {
+                repeated[kept] = true;
+                read.insert(key, i);
+            }
+            Some(_) => repeated[i] = true,
+            None => {
+                read.insert(key, i);
+            }
+        }
+    }
+    let mut index = 0;
+    commits.retain(|_| {
+        index += 1;
+        !repeated[index - 1]
+    });
+    let mut index = 0;
+    oids.retain(|_| {
+        index += 1;
+        !repeated[index - 1]
+    });
+    
+    // Union commits sharing any identity
+    let mut groups: Vec<usize> = (0..commits.len()).collect();
+    fn root(groups: &mut [usize], mut i: usize) -> usize {
+        while groups[i] != i {
+            groups[i] = groups[groups[i]];
+            i = groups[i];
+        }
+        i
+    }
+    
+    // Commits exploded from one squash share its SHA and diff, so only their
+    // messages tell copies apart
+    let mut shared_oids: HashMap<Oid, usize> = HashMap::new();
+    for oid in &oids {
+        *shared_oids.entry(*oid).or_default() += 1;
+    }
+    let exploded: Vec<bool> = oids.iter().map(|oid| shared_oids[oid] > 1).collect();
+    
+    // Only commits whose header collides with another's are worth a diff
+    let mut headers: HashMap<String, usize> = HashMap::new();
+    for (i, svcms_commit) in commits.iter().enumerate() {
+        if !exploded[i] {
+            *headers.entry(header_identity(svcms_commit)).or_default() += 1;
+        }
+    }
+    
+    let mut first_seen: HashMap<ChangeIdentity, usize> = HashMap::new();
+    for (i, svcms_commit) in commits.iter().enumerate() {
+        let mut identities = Vec::new();
         
-        // Parse commit message
-        if let Some(message) = commit.message() {
-            let timestamp = Utc.timestamp_opt(commit.time().seconds(), 0)
-                .single()
-                .unwrap_or_else(Utc::now);
-            
-            let sha = oid.to_string()[..7].to_string();
-            
-            if let Some(svcms_commit) = parse_commit_message(&sha, message, timestamp)? {
-                commits.push(svcms_commit);
+        if !exploded[i] {
+            identities.push(ChangeIdentity::Commit(oids[i]));
+            if headers[&header_identity(svcms_commit)] > 1 {
+                if let Some(patch_id) = patch_id(&repo, &repo.find_commit(oids[i])?)? {
+                    identities.push(ChangeIdentity::Patch(patch_id));
+                }
+            }
+        }
+        if let Some(source) = svcms_commit.cherry_picked_from.as_deref()
+            .and_then(|sha| repo.revparse_single(sha).ok())
+        {
+            identities.push(ChangeIdentity::Commit(source.id()));
+        }
+        // Without memories a shared header says little, e.g. `fix: typo`
+        if !svcms_commit.memory.is_empty() {
+            identities.push(ChangeIdentity::Message(message_identity(svcms_commit)));
+        }
+        
+        for identity in identities {
+            match first_seen.get(&identity) {
+                Some(&other) => {
+                    let (a, b) = (root(&mut groups, i), root(&mut groups, other));
+                    groups[a] = b;
+                }
+                None => {
+                    first_seen.insert(identity, i);
+                }
+            }
+        }
+    }
+    
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..commits.len() {
+        let group = root(&mut groups, i);
+        members.entry(group).or_default().push(i);
+    }
+    
+    let mut merged_away = vec![false; commits.len()];
+    for group in members.values() {
+        let canonical = *group.iter()
+            .min_by_key(|&&i| (commits[i].timestamp, oids[i]))
+            .expect("groups are never empty");
+        
+        let mut duplicates: Vec<String> = Vec::new();
+        for &i in group {
+            if i != canonical {
+                duplicates.push(commits[i].sha.clone());
+                merged_away[i] = true;
+            }
+            // Sources outside the walk still link the copies
+            if let Some(source) = &commits[i].cherry_picked_from {
+                duplicates.push(source.chars().take(7).collect());
+            }
+        }
+        duplicates.append(&mut commits[canonical].duplicates);
+        duplicates.retain(|sha| *sha != commits[canonical].sha);
+        duplicates.sort();
+        duplicates.dedup();
+        
+        let all_reverted = group.iter().all(|&i| commits[i].reverted_by.is_some());
+        if !all_reverted {
+            commits[canonical].reverted_by = None;
+        }
+        commits[canonical].duplicates = duplicates;
+    }
+    
+    let mut index = 0;
+    commits.retain(|_| {
+        index += 1;
+        !merged_away[index - 1]
+    });
+    
+    Ok(merged_away.iter().filter(|&&merged| merged).count())
+}
+
+/// Patch-id of a commit's diff against its first parent, `None` when it changes nothing
+fn patch_id(repo: &Repository, commit: &git2::Commit) -> Result<Option<Oid>> {
+    let tree = commit.tree()?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+    
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+    if diff.deltas().len() == 0 {
+        return Ok(None);
+    }
+    
+    Ok(Some(diff.patchid(None)?))
+}
+
+/// Header and memories with case and whitespace normalized
+fn message_identity(commit: &SvcmsCommit) -> String {
+    let mut identity = header_identity(commit);
+    for entry in &commit.memory {
+        identity.push('\n');
+        identity.push_str(&normalize_text(&entry.content));
+    }
+    identity
+}
+
+/// Normalized header of a commit
+fn header_identity(commit: &SvcmsCommit) -> String {
+    format!(
+        "{}({}): {}",
+        commit.commit_type,
+        commit.scope.as_deref().unwrap_or_default(),
+        normalize_text(&commit.summary)
+    )
+}
+
+/// Text with whitespace collapsed and lowercased, to compare messages
+fn normalize_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Get the commits in `revisions` that are annotated in its notes ref
+///
+/// Incremental syncs only walk new commits, so notes added to older ones are
+/// picked up through this instead, walking the branch without parsing the
+/// commits that have no note. An unborn HEAD has no commits.
+pub fn get_annotated_commits(repo_path: &str, revisions: &Revisions) -> Result<Vec<SvcmsCommit>> {
+    let Some(notes_ref) = &revisions.notes else { return Ok(Vec::new()) };
+    
+    let repo = Repository::open(repo_path)
+        .context("Failed to open Git repository")?;
+    let Ok(notes) = repo.notes(Some(notes_ref)) else { return Ok(Vec::new()) };
+    let mut noted = HashSet::new();
+    for note in notes {
+        let (_, commit_id) = note?;
+        noted.insert(commit_id);
+    }
+    if noted.is_empty() || (revisions.specs.is_empty() && !revisions.all && repo.head().is_err()) {
+        return Ok(Vec::new());
+    }
+    
+    let mut revwalk = repo.revwalk()?;
+    revisions.push_onto(&repo, &mut revwalk)?;
+    let mut annotated = Vec::new();
+    for oid in revwalk {
+        let oid = oid?;
+        if noted.contains(&oid) {
+            annotated.push(repo.find_commit(oid)?);
+        }
+    }
+    annotated.sort_by_key(|commit| std::cmp::Reverse(commit.time()));
+    
+    let validator = load_validator(repo_path)?;
+    let mut collector = CommitCollector::new(&repo, validator.as_ref(), revisions);
+    for commit in &annotated {
+        collector.add(commit)?;
+    }
+    
+    Ok(collector.commits)
+}
+
+/// Trailers for `synaptic annotate`: each memory followed by the location, then tags
+///
+/// The location is repeated after every memory, since a `Location:` pairs with
+/// the `Memory:` before it.
+pub fn annotation_trailers(memories: &[String], location: Option<&str>, tags: Option<&str>) -> String {
+    let mut trailers = Vec::new();
+    for memory in memories {
+        trailers.push(format!("Memory: {}", memory));
+        if let Some(location) = location {
+            trailers.push(format!("Location: {}", location));
+        }
+    }
+    if let Some(tags) = tags {
+        trailers.push(format!("Tags: {}", tags));
+    }
+    trailers.join("\n")
+}
+
+/// Append SVCMS trailers to a commit's note in the notes ref
+///
+/// Commits that aren't SVCMS-formatted get a note headed by `header` (e.g.
+/// `learned(api)`) and their own summary, so the note alone makes them SVCMS
+/// commits. Returns the annotated commit's short SHA.
+pub fn annotate_commit(repo_path: &str, rev: &str, notes_ref: &str, header: &str, trailers: &str) -> Result<String> {
+    let repo = Repository::open(repo_path)
+        .context("Failed to open Git repository")?;
+    let commit = repo.revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .with_context(|| format!("Unknown revision: {}", rev))?;
+    
+    let existing = repo.find_note(Some(notes_ref), commit.id()).ok()
+        .and_then(|note| note.message().map(str::to_string));
+    
+    let note = match existing {
+        // Keep appended trailers in the same trailer block
+        Some(existing) => format!("{}\n{}\n", existing.trim_end(), trailers.trim_end()),
+        None => {
+            let message = commit.message().unwrap_or_default();
+            let validator = load_validator(repo_path)?;
+            let is_svcms = parse_commit_message_with_validator("0000000", message, commit_time(&commit), validator.as_ref())?.is_some();
+            if is_svcms {
+                format!("{}\n", trailers.trim_end())
+            } else {
+                let summary = commit.summary().unwrap_or_default();
+                format!("{}: {}\n\n{}\n", header, summary, trailers.trim_end())
             }
         }
+    };
+    
+    let signature = repo.signature()
+        .or_else(|_| Signature::now("synaptic", "synaptic@localhost"))?;
+    repo.note(&signature, &signature, Some(notes_ref), commit.id(), &note, true)
+        .context("Failed to write note")?;
+    
+    Ok(short_sha(commit.id()))
+}
+
+/// Extract the changed files of each commit, diffing against its first parent
+///
+/// Kept separate from the walk so runs that don't need diffs don't pay for them.
+pub fn attach_changed_files(repo_path: &str, commits: &mut [SvcmsCommit]) -> Result<()> {
+    let repo = Repository::open(repo_path)
+        .context("Failed to open Git repository")?;
+    
+    for svcms_commit in commits.iter_mut().filter(|c| c.changed_files.is_none()) {
+        let commit = find_commit(&repo, svcms_commit)?;
+        svcms_commit.changed_files = Some(changed_files(&repo, &commit)?);
+    }
+    
+    Ok(())
+}
+
+/// Files changed by a commit relative to its first parent, with renames detected
+fn changed_files(repo: &Repository, commit: &git2::Commit) -> Result<Vec<FileChange>> {
+    let tree = commit.tree()?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+    
+    let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+    
+    let mut files = Vec::new();
+    for (index, delta) in diff.deltas().enumerate() {
+        let status = match delta.status() {
+            Delta::Added => ChangeStatus::Added,
+            Delta::Deleted => ChangeStatus::Deleted,
+            Delta::Renamed => ChangeStatus::Renamed,
+            Delta::Copied => ChangeStatus::Copied,
+            _ => ChangeStatus::Modified,
+        };
+        
+        let new_path = delta.new_file().path().map(|p| p.to_string_lossy().to_string());
+        let old_path = delta.old_file().path().map(|p| p.to_string_lossy().to_string());
+        let path = match status {
+            ChangeStatus::Deleted => old_path.clone(),
+            _ => new_path.or_else(|| old_path.clone()),
+        }.unwrap_or_default();
+        
+        // Binary files have no line counts
+        let (insertions, deletions) = match Patch::from_diff(&diff, index)? {
+            Some(patch) => {
+                let (_, insertions, deletions) = patch.line_stats()?;
+                (insertions, deletions)
+            }
+            None => (0, 0),
+        };
+        
+        files.push(FileChange {
+            path,
+            old_path: old_path.filter(|_| matches!(status, ChangeStatus::Renamed | ChangeStatus::Copied)),
+            status,
+            insertions,
+            deletions,
+        });
+    }
+    
+    Ok(files)
+}
+
+/// Get the raw messages of the commits in the given revisions
+///
+/// Returns `(short sha, message)` pairs, newest first. Merge commits are
+/// skipped since their messages are generated by git.
+pub fn get_commit_messages(repo_path: &str, revisions: &Revisions) -> Result<Vec<(String, String)>> {
+    let repo = Repository::open(repo_path)
+        .context("Failed to open Git repository")?;
+    
+    let mut revwalk = repo.revwalk()?;
+    revisions.push_onto(&repo, &mut revwalk)?;
+    
+    let mut messages = Vec::new();
+    
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        
+        messages.push((short_sha(commit.id()), commit.message().unwrap_or_default().to_string()));
     }
     
-    Ok(commits)
+    Ok(messages)
 }
 
 /// Print a summary of SVCMS commits
@@ -95,28 +802,89 @@ pub fn print_commit_stats(commits: &[SvcmsCommit]) {
     use colored::Colorize;
     
     let total = commits.len();
-    let with_memory = commits.iter().filter(|c| c.memory.is_some()).count();
+    let with_memory = commits.iter().filter(|c| !c.memory.is_empty()).count();
+    let breaking = commits.iter().filter(|c| c.breaking).count();
+    let reverted = commits.iter().filter(|c| c.reverted_by.is_some()).count();
     
-    // Count by type
+    // Count by type and category
     let mut type_counts = std::collections::HashMap::new();
+    let mut category_counts = std::collections::HashMap::new();
+    let mut author_counts = std::collections::HashMap::new();
     for commit in commits {
+        // Memories are credited to everyone who worked on the commit
+        if !commit.memory.is_empty() {
+            for person in commit.author.iter().chain(&commit.co_authors) {
+                *author_counts.entry(person.name.as_str()).or_insert(0) += commit.memory.len();
+            }
+        }
         *type_counts.entry(commit.commit_type.as_str()).or_insert(0) += 1;
+        if let Some(category) = &commit.category {
+            *category_counts.entry(category.as_str()).or_insert(0) += 1;
+        }
     }
     
     println!("\n📊 {} SVCMS Statistics", "Synaptic".bright_blue());
     println!("─────────────────────────");
     println!("Total SVCMS commits: {}", total.to_string().bright_green());
     println!("Commits with memories: {}", with_memory.to_string().bright_yellow());
+    println!("Breaking changes: {}", breaking.to_string().bright_red());
+    println!("Reverted: {}", reverted.to_string().bright_black());
     
     if !type_counts.is_empty() {
         println!("\nCommit types:");
         let mut types: Vec<_> = type_counts.iter().collect();
-        types.sort_by_key(|(_, count)| -(**count as i32));
+        types.sort_by_key(|(_, count)| -**count);
         
         for (commit_type, count) in types {
             println!("  {}: {}", commit_type.bright_cyan(), count);
         }
     }
+    
+    if !category_counts.is_empty() {
+        println!("\nCategories:");
+        let mut categories: Vec<_> = category_counts.iter().collect();
+        categories.sort_by_key(|(_, count)| -**count);
+        
+        for (category, count) in categories {
+            println!("  {}: {}", category.bright_magenta(), count);
+        }
+    }
+    
+    // Only when changed files were extracted
+    let changes: Vec<&FileChange> = commits.iter()
+        .filter_map(|c| c.changed_files.as_ref())
+        .flatten()
+        .collect();
+    if !changes.is_empty() {
+        let insertions: usize = changes.iter().map(|f| f.insertions).sum();
+        let deletions: usize = changes.iter().map(|f| f.deletions).sum();
+        println!("\nChanges: {} {}",
+            format!("+{}", insertions).green(),
+            format!("-{}", deletions).red()
+        );
+        
+        let mut file_counts = std::collections::HashMap::new();
+        for change in &changes {
+            *file_counts.entry(change.path.as_str()).or_insert(0) += 1;
+        }
+        let mut files: Vec<_> = file_counts.into_iter().collect();
+        files.sort_by_key(|(path, count)| (-(*count as i64), *path));
+        
+        println!("\nMost touched files:");
+        for (path, count) in files.into_iter().take(10) {
+            println!("  {}: {}", path.bright_cyan(), count);
+        }
+    }
+    
+    if !author_counts.is_empty() {
+        println!("\nMemories by author:");
+        let mut authors: Vec<_> = author_counts.iter().collect();
+        authors.sort_by_key(|(name, count)| (-(**count as i64), **name));
+        
+        for (name, count) in authors {
+            println!("  {}: {}", name.bright_green(), count);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -158,7 +926,7 @@ mod tests {
         ).unwrap();
         
         // Now test - should find no SVCMS commits (initial commit isn't SVCMS)
-        let commits = get_svcms_commits(dir.path().to_str().unwrap(), 10).unwrap();
+        let commits = get_svcms_commits(dir.path().to_str().unwrap(), &Revisions::head(), 10).unwrap();
         assert_eq!(commits.len(), 0);
     }
     
@@ -195,10 +963,509 @@ mod tests {
         ).unwrap();
         
         // Should find one SVCMS commit
-        let commits = get_svcms_commits(dir.path().to_str().unwrap(), 10).unwrap();
+        let commits = get_svcms_commits(dir.path().to_str().unwrap(), &Revisions::head(), 10).unwrap();
         assert_eq!(commits.len(), 1);
         assert_eq!(commits[0].commit_type, "feat");
         assert_eq!(commits[0].scope, Some("test".to_string()));
-        assert_eq!(commits[0].memory, Some("Test memory content".to_string()));
+        assert_eq!(commits[0].memory[0].content, "Test memory content");
+    }
+    
+    #[test]
+    fn test_get_svcms_commits_uses_validator() {
+        let (dir, repo) = create_test_repo().unwrap();
+        
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let tree_id = {
+            let mut index = repo.index().unwrap();
+            index.write_tree().unwrap()
+        };
+        let tree = repo.find_tree(tree_id).unwrap();
+        
+        let first = repo.commit(
+            Some("HEAD"),
+            &sig,
+            &sig,
+            "knowledge.learned(auth): tokens expire after 24h\n\nMemory: JWT expiry is 24h",
+            &tree,
+            &[],
+        ).unwrap();
+        let first_commit = repo.find_commit(first).unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &sig,
+            &sig,
+            "meta.workflow(auth): not allowed in auth",
+            &tree,
+            &[&first_commit],
+        ).unwrap();
+        
+        let config: crate::config::CommitTypesConfig = toml::from_str(r#"
+[scopes.modules]
+auth = { categories = ["knowledge"], custom_types = [] }
+"#).unwrap();
+        let validator = CommitTypeValidator::from_config(&config);
+        
+        let commits = get_svcms_commits_with_validator(dir.path().to_str().unwrap(), &Revisions::head(), 10, Some(&validator)).unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].category, Some("knowledge".to_string()));
+        assert_eq!(commits[0].commit_type, "learned");
+        assert_eq!(commits[0].memory[0].content, "JWT expiry is 24h");
+    }
+    
+    #[test]
+    fn test_get_svcms_commits_resolves_people() {
+        let (dir, repo) = create_test_repo().unwrap();
+        
+        // Commit the .mailmap so it is read from HEAD
+        std::fs::write(dir.path().join(".mailmap"), "Ada Lovelace <ada@example.com> <ada@old.example.com>\n").unwrap();
+        let tree_id = {
+            let mut index = repo.index().unwrap();
+            index.add_path(std::path::Path::new(".mailmap")).unwrap();
+            index.write_tree().unwrap()
+        };
+        let tree = repo.find_tree(tree_id).unwrap();
+        
+        let author = git2::Signature::now("ada", "ada@old.example.com").unwrap();
+        let committer = git2::Signature::now("Test User", "test@example.com").unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &author,
+            &committer,
+            "learned(api): pair debugging\n\nMemory: Logs lie\nCo-authored-by: A. L. <ada@old.example.com>\nCo-authored-by: Grace",
+            &tree,
+            &[],
+        ).unwrap();
+        
+        let commits = get_svcms_commits(dir.path().to_str().unwrap(), &Revisions::head(), 10).unwrap();
+        let ada = Person::new("Ada Lovelace", Some("ada@example.com".to_string()));
+        assert_eq!(commits[0].author, Some(ada.clone()));
+        assert_eq!(commits[0].committer, Some(Person::new("Test User", Some("test@example.com".to_string()))));
+        assert_eq!(commits[0].co_authors, vec![ada, Person::new("Grace", None)]);
+    }
+    
+    #[test]
+    fn test_get_svcms_commits_between_dates() {
+        let (dir, repo) = create_test_repo().unwrap();
+        let tree_id = {
+            let mut index = repo.index().unwrap();
+            index.write_tree().unwrap()
+        };
+        let tree = repo.find_tree(tree_id).unwrap();
+        
+        // Commit at a given time, in UTC-5
+        let commit = |message: &str, time: &str, parents: &[Oid]| {
+            let time = DateTime::parse_from_rfc3339(time).unwrap();
+            let sig = git2::Signature::new("Test User", "test@example.com", &git2::Time::new(time.timestamp(), -300)).unwrap();
+            let parents: Vec<git2::Commit> = parents.iter().map(|p| repo.find_commit(*p).unwrap()).collect();
+            let parents: Vec<&git2::Commit> = parents.iter().collect();
+            repo.commit(None, &sig, &sig, message, &tree, &parents).unwrap()
+        };
+        
+        let base = commit("feat(core): base", "2026-01-01T10:00:00-05:00", &[]);
+        // A branch commit with a newer date, merged after an older-dated main commit
+        let skewed = commit("feat(core): old clock", "2025-06-01T10:00:00-05:00", &[base]);
+        let branch = commit("feat(ui): branch work", "2026-03-01T10:00:00-05:00", &[base]);
+        let merge = commit("Merge branch 'ui'", "2026-03-02T10:00:00-05:00", &[skewed, branch]);
+        repo.reference("refs/heads/main", merge, true, "test").unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        
+        let repo_path = dir.path().to_str().unwrap();
+        let since = |since: &str, until: Option<&str>| -> Vec<String> {
+            let dates = DateRange {
+                since: Some(DateTime::parse_from_rfc3339(since).unwrap()),
+                until: until.map(|u| DateTime::parse_from_rfc3339(u).unwrap()),
+            };
+            get_svcms_commits_between(repo_path, &Revisions::head(), &dates).unwrap()
+                .into_iter().map(|c| c.summary).collect()
+        };
+        
+        // The old-dated commit doesn't stop the walk before the base commit
+        assert_eq!(since("2025-12-01T00:00:00Z", None), vec!["branch work", "base"]);
+        assert_eq!(since("2025-12-01T00:00:00Z", Some("2026-02-01T00:00:00Z")), vec!["base"]);
+        
+        // The committer's timezone is kept
+        let commits = since("2026-02-01T00:00:00Z", None);
+        assert_eq!(commits, vec!["branch work"]);
+        let branch_commit = &get_svcms_commits(repo_path, &Revisions::head(), 10).unwrap()
+            .into_iter().find(|c| c.summary == "branch work").unwrap();
+        assert_eq!(branch_commit.timestamp.to_rfc3339(), "2026-03-01T10:00:00-05:00");
+    }
+    
+    #[test]
+    fn test_attach_changed_files() {
+        let (dir, repo) = create_test_repo().unwrap();
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        
+        let commit = |message: &str, files: &[(&str, &str)], removed: &[&str]| {
+            for (path, content) in files {
+                let full_path = dir.path().join(path);
+                std::fs::create_dir_all(full_path.parent().unwrap()).unwrap();
+                std::fs::write(full_path, content).unwrap();
+            }
+            let mut index = repo.index().unwrap();
+            for path in removed {
+                std::fs::remove_file(dir.path().join(path)).unwrap();
+                index.remove_path(std::path::Path::new(path)).unwrap();
+            }
+            for (path, _) in files {
+                index.add_path(std::path::Path::new(path)).unwrap();
+            }
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parents: Vec<git2::Commit> = repo.head().ok()
+                .map(|head| head.peel_to_commit().unwrap())
+                .into_iter().collect();
+            let parents: Vec<&git2::Commit> = parents.iter().collect();
+            repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents).unwrap()
+        };
+        
+        let body = "fn main() {\n    one();\n    two();\n    three();\n}\n";
+        commit("feat(core): start", &[("src/main.rs", body), ("notes.txt", "a\nb\n")], &[]);
+        commit(
+            "refactor(core): move main\n\nMemory: Entry point lives in src/bin",
+            &[("src/bin/app.rs", body), ("notes.txt", "a\nc\nd\n")],
+            &["src/main.rs"],
+        );
+        
+        let repo_path = dir.path().to_str().unwrap();
+        let mut commits = get_svcms_commits(repo_path, &Revisions::head(), 10).unwrap();
+        assert!(commits.iter().all(|c| c.changed_files.is_none()));
+        
+        attach_changed_files(repo_path, &mut commits).unwrap();
+        
+        let mut refactor = commits[0].changed_files.clone().unwrap();
+        refactor.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(refactor, vec![
+            FileChange { path: "notes.txt".to_string(), old_path: None, status: ChangeStatus::Modified, insertions: 2, deletions: 1 },
+            FileChange {
+                path: "src/bin/app.rs".to_string(),
+                old_path: Some("src/main.rs".to_string()),
+                status: ChangeStatus::Renamed,
+                insertions: 0,
+                deletions: 0,
+            },
+        ]);
+        
+        // The root commit diffs against the empty tree
+        let start = commits[1].changed_files.as_ref().unwrap();
+        assert!(start.iter().all(|f| f.status == ChangeStatus::Added));
+        assert_eq!(start.iter().map(|f| f.insertions).sum::<usize>(), 7);
+    }
+    
+    #[test]
+    fn test_get_svcms_commits_links_reverts() {
+        let (dir, repo) = create_test_repo().unwrap();
+        
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let tree_id = {
+            let mut index = repo.index().unwrap();
+            index.write_tree().unwrap()
+        };
+        let tree = repo.find_tree(tree_id).unwrap();
+        
+        let commit = |message: &str, parents: &[Oid]| {
+            let parents: Vec<git2::Commit> = parents.iter().map(|p| repo.find_commit(*p).unwrap()).collect();
+            let parents: Vec<&git2::Commit> = parents.iter().collect();
+            repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents).unwrap()
+        };
+        
+        let original = commit("learned(api): retry on 429\n\nMemory: Retry 429s immediately", &[]);
+        let kept = commit("learned(api): cache tokens\n\nMemory: Tokens are cacheable", &[original]);
+        let revert = commit(
+            &format!("Revert \"learned(api): retry on 429\"\n\nThis reverts commit {}.", original),
+            &[kept],
+        );
+        
+        let repo_path = dir.path().to_str().unwrap();
+        let commits = get_svcms_commits(repo_path, &Revisions::head(), 10).unwrap();
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].reverted_by, None);
+        assert_eq!(commits[1].reverted_by, Some(short_sha(revert)));
+        
+        // The original is pulled in even when it is outside the walked window
+        let commits = get_svcms_commits(repo_path, &Revisions::head(), 1).unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].sha, short_sha(original));
+        assert_eq!(commits[0].reverted_by, Some(short_sha(revert)));
+        
+        // Reverting the revert brings the memory back
+        commit(
+            &format!("Revert \"Revert \"learned(api): retry on 429\"\"\n\nThis reverts commit {}.", revert),
+            &[revert],
+        );
+        let commits = get_svcms_commits(repo_path, &Revisions::head(), 10).unwrap();
+        assert!(commits.iter().all(|c| c.reverted_by.is_none()));
+    }
+    
+    #[test]
+    fn test_find_commit_prefers_full_sha() {
+        let (_dir, repo) = create_test_repo().unwrap();
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let tree = repo.find_tree(repo.treebuilder(None).unwrap().write().unwrap()).unwrap();
+        let oid = repo.commit(Some("HEAD"), &sig, &sig, "learned: x\n\nMemory: m", &tree, &[]).unwrap();
+        
+        let mut svcms_commit = crate::parser::parse_commit_message("0000000", "learned: x\n\nMemory: m", chrono::Utc::now()).unwrap().unwrap();
+        assert!(find_commit(&repo, &svcms_commit).is_err());
+        // The short SHA isn't consulted once the full SHA is known
+        svcms_commit.full_sha = Some(oid.to_string());
+        assert_eq!(find_commit(&repo, &svcms_commit).unwrap().id(), oid);
+    }
+    
+    #[test]
+    fn test_deduplicate_commits() {
+        let (dir, repo) = create_test_repo().unwrap();
+        
+        let tree = |files: &[(&str, &str)]| {
+            let mut builder = repo.treebuilder(None).unwrap();
+            for (path, content) in files {
+                let blob = repo.blob(content.as_bytes()).unwrap();
+                builder.insert(path, blob, 0o100644).unwrap();
+            }
+            builder.write().unwrap()
+        };
+        let commit = |branch: &str, message: &str, time: i64, files: &[(&str, &str)], parents: &[Oid]| {
+            let sig = git2::Signature::new("Test User", "test@example.com", &git2::Time::new(time, 0)).unwrap();
+            let tree = repo.find_tree(tree(files)).unwrap();
+            let parents: Vec<git2::Commit> = parents.iter().map(|p| repo.find_commit(*p).unwrap()).collect();
+            let parents: Vec<&git2::Commit> = parents.iter().collect();
+            let oid = repo.commit(None, &sig, &sig, message, &tree, &parents).unwrap();
+            repo.reference(&format!("refs/heads/{}", branch), oid, true, "test").unwrap();
+            oid
+        };
+        
+        let base = commit("main", "feat(api): base", 1_000, &[("api.rs", "one\n")], &[]);
+        let fix = commit("main", "fix(api): add two\n\nMemory: Two matters", 2_000, &[("api.rs", "one\ntwo\n")], &[base]);
+        let prep = commit("release", "chore(release): prepare", 3_000, &[("api.rs", "one\n"), ("VERSION", "1.0\n")], &[base]);
+        let picked = commit(
+            "release",
+            &format!("fix(api): add two\n\nMemory: Two matters\n(cherry picked from commit {})", fix),
+            4_000,
+            &[("api.rs", "one\ntwo\n"), ("VERSION", "1.0\n")],
+            &[prep],
+        );
+        // Lost its footer while rebasing, same header and diff
+        let rebased = commit("hotfix", "fix(api): add two", 5_000, &[("api.rs", "one\ntwo\n")], &[base]);
+        // Same header, different diff: not a copy
+        let unrelated = commit("later", "fix(api): add two", 5_500, &[("api.rs", "two\n")], &[base]);
+        // Same memory and header in another change
+        let restated = commit("docs", "fix(api): Add  two\n\nMemory: two matters", 6_000, &[("api.rs", "one\n"), ("README", "two\n")], &[base]);
+        
+        let repo_path = dir.path().to_str().unwrap();
+        let mut commits = get_svcms_commits(repo_path, &Revisions::new(vec![], true), 10).unwrap();
+        assert_eq!(commits.len(), 7);
+        
+        // The same commit read twice is kept once, not counted as a copy
+        commits.push(commits[0].clone());
+        let merged = deduplicate_commits(repo_path, &mut commits).unwrap();
+        assert_eq!(merged, 3);
+        
+        let shas: Vec<&str> = commits.iter().map(|c| c.sha.as_str()).collect();
+        assert_eq!(shas.len(), 4);
+        assert!(shas.contains(&short_sha(base).as_str()));
+        assert!(shas.contains(&short_sha(prep).as_str()));
+        assert!(shas.contains(&short_sha(unrelated).as_str()));
+        
+        // The oldest copy is canonical and lists the others
+        let canonical = commits.iter().find(|c| c.sha == short_sha(fix)).unwrap();
+        let mut expected = vec![short_sha(picked), short_sha(rebased), short_sha(restated)];
+        expected.sort();
+        assert_eq!(canonical.duplicates, expected);
+        
+        // A cherry-pick source outside the walk is still linked
+        let mut commits = get_svcms_commits(repo_path, &Revisions::new(vec!["release".to_string(), "hotfix".to_string()], false), 10).unwrap();
+        deduplicate_commits(repo_path, &mut commits).unwrap();
+        let canonical = commits.iter().find(|c| c.sha == short_sha(picked)).unwrap();
+        let mut expected = vec![short_sha(fix), short_sha(rebased)];
+        expected.sort();
+        assert_eq!(canonical.duplicates, expected);
+    }
+    
+    #[test]
+    fn test_merge_policies() {
+        let (dir, repo) = create_test_repo().unwrap();
+        
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let tree_id = {
+            let mut index = repo.index().unwrap();
+            index.write_tree().unwrap()
+        };
+        let tree = repo.find_tree(tree_id).unwrap();
+        
+        let commit = |update_head: bool, message: &str, parents: &[Oid]| {
+            let parents: Vec<git2::Commit> = parents.iter().map(|p| repo.find_commit(*p).unwrap()).collect();
+            let parents: Vec<&git2::Commit> = parents.iter().collect();
+            repo.commit(update_head.then_some("HEAD"), &sig, &sig, message, &tree, &parents).unwrap()
+        };
+        
+        let base = commit(true, "feat(core): base", &[]);
+        let branch = commit(false, "feat(ui): branch work\n\nMemory: Buttons debounce clicks", &[base]);
+        let merge = commit(
+            true,
+            "Merge pull request #7 from acme/ui\n\nfeat(ui): new toolbar\n\nMemory: Toolbar state lives in the URL",
+            &[base, branch],
+        );
+        let squash = commit(
+            true,
+            "Rate limiting (#8)\n\n* feat(api): add limiter\n\nMemory: Limits reset each minute\n\n* fix(api): window bounds\n\nMemory: Windows are half-open",
+            &[merge],
+        );
+        
+        let repo_path = dir.path().to_str().unwrap();
+        let summaries = |merges: MergePolicy| -> Vec<(String, String)> {
+            let revisions = Revisions::head().with_merge_policy(merges);
+            get_svcms_commits(repo_path, &revisions, 10).unwrap()
+                .into_iter().map(|c| (c.sha, c.summary)).collect()
+        };
+        
+        // By default merge and squash messages aren't SVCMS
+        let mut default = summaries(MergePolicy::default());
+        default.sort_by(|a, b| a.1.cmp(&b.1));
+        assert_eq!(default, vec![
+            (short_sha(base), "base".to_string()),
+            (short_sha(branch), "branch work".to_string()),
+        ]);
+        
+        assert_eq!(summaries(MergePolicy { first_parent: true, ..Default::default() }), vec![
+            (short_sha(base), "base".to_string()),
+        ]);
+        
+        let exploded = summaries(MergePolicy { first_parent: true, explode_squash: true, ..Default::default() });
+        assert_eq!(exploded, vec![
+            (short_sha(squash), "add limiter".to_string()),
+            (short_sha(squash), "window bounds".to_string()),
+            (short_sha(merge), "new toolbar".to_string()),
+            (short_sha(base), "base".to_string()),
+        ]);
+        
+        // Each piece numbers its memories from its place in the message
+        let revisions = Revisions::head().with_merge_policy(MergePolicy { explode_squash: true, ..Default::default() });
+        let offsets: Vec<usize> = get_svcms_commits(repo_path, &revisions, 1).unwrap()
+            .iter().map(|c| c.memory_offset).collect();
+        assert_eq!(offsets, vec![0, 1]);
+        
+        let no_merges = summaries(MergePolicy { ignore_merges: true, explode_squash: true, ..Default::default() });
+        assert!(!no_merges.iter().any(|(sha, _)| *sha == short_sha(merge)));
+        assert_eq!(no_merges.len(), 4);
+        
+        // Exploded commits sharing a SHA aren't merged as copies of each other
+        let mut commits = get_svcms_commits(repo_path, &Revisions::head().with_merge_policy(MergePolicy {
+            explode_squash: true,
+            ..Default::default()
+        }), 10).unwrap();
+        assert_eq!(deduplicate_commits(repo_path, &mut commits).unwrap(), 0);
+    }
+    
+    #[test]
+    fn test_annotation_trailers_pair_location_with_each_memory() {
+        let memories = vec!["Retry after the reset".to_string(), "Log every 429".to_string()];
+        let trailers = annotation_trailers(&memories, Some("src/http/CLAUDE.md"), Some("http"));
+        let message = format!("learned(http): x\n\n{}", trailers);
+        let commit = crate::parser::parse_commit_message("abc1234", &message, chrono::Utc::now()).unwrap().unwrap();
+        
+        assert_eq!(commit.memory.len(), 2);
+        assert!(commit.memory.iter().all(|m| m.location.as_deref() == Some("src/http/CLAUDE.md")));
+        assert_eq!(commit.tags, vec!["http".to_string()]);
+        assert_eq!(annotation_trailers(&memories, None, None), "Memory: Retry after the reset\nMemory: Log every 429");
+    }
+    
+    #[test]
+    fn test_notes_annotate_commits() {
+        let (dir, repo) = create_test_repo().unwrap();
+        
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let tree_id = {
+            let mut index = repo.index().unwrap();
+            index.write_tree().unwrap()
+        };
+        let tree = repo.find_tree(tree_id).unwrap();
+        
+        let commit = |message: &str, parents: &[Oid]| {
+            let parents: Vec<git2::Commit> = parents.iter().map(|p| repo.find_commit(*p).unwrap()).collect();
+            let parents: Vec<&git2::Commit> = parents.iter().collect();
+            repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents).unwrap()
+        };
+        
+        let plain = commit("Bump timeout to 30s", &[]);
+        let svcms = commit("fix(api): handle 429", &[plain]);
+        commit("docs: readme", &[svcms]);
+        
+        let repo_path = dir.path().to_str().unwrap();
+        annotate_commit(repo_path, &short_sha(svcms), DEFAULT_NOTES_REF, "learned", "Memory: Retry after the reset").unwrap();
+        annotate_commit(repo_path, &short_sha(svcms), DEFAULT_NOTES_REF, "learned", "Memory: Log every 429").unwrap();
+        annotate_commit(repo_path, &short_sha(plain), DEFAULT_NOTES_REF, "learned(http)", "Memory: The gateway times out at 29s").unwrap();
+        
+        let note = repo.find_note(Some(DEFAULT_NOTES_REF), plain).unwrap();
+        assert_eq!(note.message().unwrap(), "learned(http): Bump timeout to 30s\n\nMemory: The gateway times out at 29s\n");
+        
+        // Without the notes ref, notes are ignored
+        let commits = get_svcms_commits(repo_path, &Revisions::head(), 10).unwrap();
+        assert!(commits.iter().all(|c| c.memory.is_empty()));
+        
+        let revisions = Revisions::head().with_notes(Some(DEFAULT_NOTES_REF.to_string()));
+        let commits = get_svcms_commits(repo_path, &revisions, 10).unwrap();
+        assert_eq!(commits.len(), 3);
+        let memories: Vec<&str> = commits[1].memory.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(memories, vec!["Retry after the reset", "Log every 429"]);
+        assert_eq!(commits[2].sha, short_sha(plain));
+        assert_eq!(commits[2].scope.as_deref(), Some("http"));
+        assert_eq!(commits[2].summary, "Bump timeout to 30s");
+        
+        // Annotated commits are found without parsing the others
+        let annotated = get_annotated_commits(repo_path, &revisions).unwrap();
+        let shas: Vec<String> = annotated.iter().map(|c| c.sha.clone()).collect();
+        assert_eq!(shas.len(), 2);
+        assert!(shas.contains(&short_sha(plain)) && shas.contains(&short_sha(svcms)));
+        
+        // Only within the requested revisions
+        let range = Revisions::new(vec![format!("{}..HEAD", plain)], false).with_notes(Some(DEFAULT_NOTES_REF.to_string()));
+        let annotated = get_annotated_commits(repo_path, &range).unwrap();
+        let shas: Vec<String> = annotated.iter().map(|c| c.sha.clone()).collect();
+        assert_eq!(shas, vec![short_sha(svcms)]);
+        
+        // An unborn HEAD has no commits
+        repo.set_head("refs/heads/unborn").unwrap();
+        assert!(get_annotated_commits(repo_path, &revisions).unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_get_svcms_commits_in_revision_ranges() {
+        let (dir, repo) = create_test_repo().unwrap();
+        
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let tree_id = {
+            let mut index = repo.index().unwrap();
+            index.write_tree().unwrap()
+        };
+        let tree = repo.find_tree(tree_id).unwrap();
+        
+        let commit = |update_ref: &str, message: &str, parent: Option<Oid>| {
+            let parent = parent.map(|p| repo.find_commit(p).unwrap());
+            let parents: Vec<&git2::Commit> = parent.iter().collect();
+            repo.commit(Some(update_ref), &sig, &sig, message, &tree, &parents).unwrap()
+        };
+        
+        // main: base - release; feature branches off base
+        let base = commit("HEAD", "feat(core): base", None);
+        let release = commit("HEAD", "feat(core): release", Some(base));
+        repo.tag_lightweight("v1.0.0", &repo.find_object(base, None).unwrap(), false).unwrap();
+        commit("refs/heads/feature", "feat(ui): feature", Some(base));
+        
+        let repo_path = dir.path().to_str().unwrap();
+        let summaries = |specs: &[&str], all: bool| -> Vec<String> {
+            let revisions = Revisions::new(specs.iter().map(|s| s.to_string()).collect(), all);
+            let mut summaries: Vec<String> = get_svcms_commits(repo_path, &revisions, 100).unwrap()
+                .into_iter().map(|c| c.summary).collect();
+            summaries.sort();
+            summaries
+        };
+        
+        assert_eq!(summaries(&[], false), vec!["base", "release"]);
+        assert_eq!(summaries(&["feature"], false), vec!["base", "feature"]);
+        assert_eq!(summaries(&["v1.0.0..HEAD"], false), vec!["release"]);
+        assert_eq!(summaries(&[&format!("{}...feature", release)], false), vec!["feature", "release"]);
+        assert_eq!(summaries(&["feature", "^v1.0.0"], false), vec!["feature"]);
+        assert_eq!(summaries(&["HEAD", "feature"], false), vec!["base", "feature", "release"]);
+        assert_eq!(summaries(&[], true), vec!["base", "feature", "release"]);
+        assert!(get_svcms_commits(repo_path, &Revisions::new(vec!["nope".to_string()], false), 10).is_err());
     }
 }
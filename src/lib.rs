@@ -4,23 +4,186 @@ pub mod git;
 pub mod obsidian;
 pub mod config;
 pub mod commit_types;
+pub mod diagnostics;
+pub mod lint;
+pub mod writer;
+pub mod state;
+pub mod dates;
+pub mod source;
+pub mod trust;
+pub mod locations;
+pub mod cleanup;
+pub mod validate;
 
 // Re-export for easier access
 pub use git::*;
 pub use memory::*;
 pub use obsidian::*;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct SvcmsCommit {
     pub sha: String,
+    /// Full SHA, when the source knows it
+    pub full_sha: Option<String>,
+    pub category: Option<String>,
     pub commit_type: String,
     pub scope: Option<String>,
     pub summary: String,
+    pub breaking: bool,
+    pub breaking_change: Option<String>,
     pub body: Option<String>,
-    pub memory: Option<String>,
+    pub memory: Vec<MemoryEntry>,
+    /// Position of the first memory in the commit message; squash pieces
+    /// after the first start past the memories of the pieces before them
+    #[serde(default)]
+    pub memory_offset: usize,
     pub location: Option<String>,
     pub context: Option<String>,
-    pub refs: Vec<String>,
+    pub refs: Vec<Reference>,
     pub tags: Vec<String>,
-    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Trailers Synaptic doesn't interpret (`Signed-off-by`, `Reviewed-by`, ...)
+    /// in message order, each token with all its values
+    pub footers: indexmap::IndexMap<String, Vec<String>>,
+    /// Commit time in the committer's own timezone
+    pub timestamp: chrono::DateTime<chrono::FixedOffset>,
+    /// Commit author after `.mailmap`, set by the git walker
+    pub author: Option<Person>,
+    /// Commit committer after `.mailmap`, set by the git walker
+    pub committer: Option<Person>,
+    /// People from `Co-authored-by:` trailers, in message order
+    pub co_authors: Vec<Person>,
+    /// Files changed relative to the first parent; `None` unless extracted
+    /// with `git::attach_changed_files`
+    pub changed_files: Option<Vec<FileChange>>,
+    /// Short SHA of the commit that reverted this one, set by the git walker
+    pub reverted_by: Option<String>,
+    /// SHA from a `(cherry picked from commit <sha>)` line
+    pub cherry_picked_from: Option<String>,
+    /// Short SHAs of other copies of this change (cherry-picks, rebases),
+    /// set by `git::deduplicate_commits`
+    pub duplicates: Vec<String>,
+}
+
+impl SvcmsCommit {
+    /// Names of everyone credited with the commit: the author, then co-authors
+    pub fn author_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for person in self.author.iter().chain(&self.co_authors) {
+            if !names.contains(&person.name) {
+                names.push(person.name.clone());
+            }
+        }
+        names
+    }
+}
+
+/// A single `Memory:` footer, optionally paired with its own `Location:`
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct MemoryEntry {
+    pub content: String,
+    pub location: Option<String>,
+}
+
+/// How a file changed in a commit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeStatus {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+    Copied,
+}
+
+/// One file a commit changed, with its line counts
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FileChange {
+    /// Repository-relative path after the change (before it, for deletions)
+    pub path: String,
+    /// Previous path of a renamed or copied file
+    pub old_path: Option<String>,
+    pub status: ChangeStatus,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// A person from a git signature or a `Name <email>` trailer
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Person {
+    pub name: String,
+    pub email: Option<String>,
+}
+
+impl Person {
+    pub fn new(name: impl Into<String>, email: Option<String>) -> Self {
+        Person { name: name.into(), email }
+    }
+
+    /// Parse `Name <email>`; the email is optional
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        match value.strip_suffix('>').and_then(|rest| rest.rsplit_once('<')) {
+            Some((name, email)) => Person::new(name.trim(), Some(email.trim().to_string())),
+            None => Person::new(value, None),
+        }
+    }
+}
+
+impl std::fmt::Display for Person {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.email {
+            Some(email) => write!(f, "{} <{}>", self.name, email),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// A `Refs:` entry, classified by what it points at
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Reference {
+    /// `#87`
+    Issue { number: u64 },
+    /// Repository-relative path, optionally with a line (`src/api/client.ts:42`)
+    File { path: String, line: Option<u32> },
+    /// Commit SHA (abbreviated or full)
+    Commit { sha: String },
+    /// `http(s)://` link
+    Url { url: String },
+    /// Anything else, kept verbatim
+    Text { text: String },
+}
+
+impl Reference {
+    /// Reference type name, matching the `ref_type` column of the design doc schema
+    pub fn ref_type(&self) -> &'static str {
+        match self {
+            Reference::Issue { .. } => "issue",
+            Reference::File { .. } => "doc",
+            Reference::Commit { .. } => "commit",
+            Reference::Url { .. } => "url",
+            Reference::Text { .. } => "text",
+        }
+    }
+
+    /// Link to an issue using a tracker template with a `{number}` placeholder
+    pub fn issue_url(&self, template: &str) -> Option<String> {
+        match self {
+            Reference::Issue { number } => Some(template.replace("{number}", &number.to_string())),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Reference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reference::Issue { number } => write!(f, "#{}", number),
+            Reference::File { path, line: Some(line) } => write!(f, "{}:{}", path, line),
+            Reference::File { path, line: None } => write!(f, "{}", path),
+            Reference::Commit { sha } => write!(f, "{}", sha),
+            Reference::Url { url } => write!(f, "{}", url),
+            Reference::Text { text } => write!(f, "{}", text),
+        }
+    }
 }
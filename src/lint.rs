@@ -0,0 +1,260 @@
+//! Lint commit messages against the SVCMS specification
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context, anyhow};
+use colored::Colorize;
+use crate::commit_types::CommitTypeValidator;
+use crate::diagnostics::{Diagnostic, DiagnosticKind, Severity, Span};
+use crate::parser::{parse_commit_message_with_diagnostics, split_trailers, fold, trailer_value_span};
+
+/// Summaries longer than this get a warning (spec: "max 72 chars")
+pub const MAX_SUMMARY_LENGTH: usize = 72;
+
+/// Memories longer than this are unlikely to be concise insights
+pub const MAX_MEMORY_LENGTH: usize = 200;
+
+/// Git's scissors line after the comment string; everything below it is dropped from the message
+const SCISSORS: &str = " ------------------------ >8 ------------------------";
+
+/// Characters git picks the comment character from for `core.commentChar = auto`
+const AUTO_COMMENT_CHARS: &str = "#;@!$%^&|:";
+
+/// Lint a single commit message
+///
+/// Messages git generates itself (merges, reverts, fixup!/squash!) are
+/// accepted as-is so the linter can run as a `commit-msg` hook. Comments in
+/// a message being edited must be removed first with `strip_comments`.
+pub fn lint_message(message: &str, validator: Option<&CommitTypeValidator>) -> Vec<Diagnostic> {
+    if is_git_generated(message) {
+        return Vec::new();
+    }
+
+    let outcome = parse_commit_message_with_diagnostics("0000000", message, chrono::Utc::now(), validator);
+    let mut diagnostics = outcome.diagnostics;
+
+    if let Some(commit) = &outcome.commit {
+        let lines: Vec<&str> = message.lines().collect();
+        let header = lines.first().copied().unwrap_or_default();
+
+        // Summary length
+        let summary_length = commit.summary.chars().count();
+        if summary_length > MAX_SUMMARY_LENGTH {
+            let column = header.chars().count() - summary_length + 1;
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::SummaryTooLong { length: summary_length },
+                Span::new(1, column, summary_length),
+            ));
+        }
+
+        // Memory best practices: concise, and more than a restated summary
+        let (_, trailers) = split_trailers(&lines[1..]);
+        for trailer in trailers.iter().filter(|t| t.token.eq_ignore_ascii_case("memory") && !t.value.is_empty()) {
+            let memory_length = trailer.value.chars().count();
+            if memory_length > MAX_MEMORY_LENGTH {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::MemoryTooLong { length: memory_length },
+                    trailer_value_span(trailer),
+                ));
+            }
+            if fold(&trailer.value).eq_ignore_ascii_case(commit.summary.trim()) {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::MemoryRepeatsSummary,
+                    trailer_value_span(trailer),
+                ));
+            }
+        }
+
+        // Knowledge commits exist to be remembered
+        if commit.category.as_deref() == Some("knowledge") && commit.memory.is_empty() {
+            let type_length = header.find(['(', '!', ':']).unwrap_or(0);
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::MissingMemory { commit_type: commit.commit_type.clone() },
+                Span::new(1, 1, type_length),
+            ).with_help("add `Memory: <key insight for future sessions>` as a footer"));
+        }
+    }
+
+    diagnostics.sort_by_key(|d| (d.span.line, d.span.column));
+    diagnostics
+}
+
+/// Check whether diagnostics should fail the lint
+pub fn has_failures(diagnostics: &[Diagnostic], strict: bool) -> bool {
+    diagnostics.iter().any(|d| d.is_error() || strict)
+}
+
+/// Comment string git uses in commit message files for the repository at
+/// `repo_path`: `core.commentString`, else `core.commentChar`, else `#`
+///
+/// May be `auto`, which `strip_comments` resolves per message.
+pub fn comment_string(repo_path: &Path) -> String {
+    let config = git2::Repository::discover(repo_path)
+        .and_then(|repo| repo.config())
+        .or_else(|_| git2::Config::open_default());
+    config.ok()
+        .and_then(|config| {
+            config.get_string("core.commentString")
+                .or_else(|_| config.get_string("core.commentChar"))
+                .ok()
+        })
+        .filter(|comment| !comment.is_empty())
+        .unwrap_or_else(|| "#".to_string())
+}
+
+/// Remove the comment lines of a commit message file and everything below
+/// the scissors line
+///
+/// With `auto`, the comment character is the first of git's candidates that
+/// starts a comment-like line (`<char> text`, or the character alone).
+pub fn strip_comments(message: &str, comment: &str) -> String {
+    let auto;
+    let comment = if comment == "auto" {
+        auto = AUTO_COMMENT_CHARS.chars()
+            .find(|c| message.lines().any(|line| line.strip_prefix(*c).is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))))
+            .unwrap_or('#')
+            .to_string();
+        auto.as_str()
+    } else {
+        comment
+    };
+    let scissors = format!("{}{}", comment, SCISSORS);
+
+    message.lines()
+        .take_while(|line| *line != scissors)
+        .filter(|line| !line.starts_with(comment))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_start_matches('\n')
+        .to_string()
+}
+
+/// Check if a message was generated by git rather than written by a person
+fn is_git_generated(message: &str) -> bool {
+    let header = message.lines().next().unwrap_or_default();
+    ["Merge ", "Revert \"", "fixup! ", "squash! ", "amend! "]
+        .iter()
+        .any(|prefix| header.starts_with(prefix))
+}
+
+/// Print diagnostics for one message, with the offending line and a caret
+pub fn print_diagnostics(source: &str, message: &str, diagnostics: &[Diagnostic]) {
+    let lines: Vec<&str> = message.lines().collect();
+
+    if diagnostics.is_empty() {
+        println!("{} {}", "✓".green(), source);
+        return;
+    }
+
+    let marker = if diagnostics.iter().any(|d| d.is_error()) {
+        "✗".red()
+    } else {
+        "⚠".yellow()
+    };
+    println!("{} {}", marker, source);
+
+    for diagnostic in diagnostics {
+        let label = match diagnostic.severity {
+            Severity::Error => "error".red().bold(),
+            Severity::Warning => "warning".yellow().bold(),
+        };
+        println!("  {}:{}: {}: {}", diagnostic.span.line, diagnostic.span.column, label, diagnostic.kind);
+
+        if let Some(line) = lines.get(diagnostic.span.line.saturating_sub(1)) {
+            println!("    {}", line.bright_black());
+            println!(
+                "    {}{}",
+                " ".repeat(diagnostic.span.column.saturating_sub(1)),
+                "^".repeat(diagnostic.span.length.max(1)).bright_red()
+            );
+        }
+
+        if let Some(help) = &diagnostic.help {
+            println!("    {} {}", "help:".bright_cyan(), help);
+        }
+    }
+}
+
+/// Install `synaptic lint` as the repository's `commit-msg` hook
+pub fn install_commit_msg_hook(repo_path: &Path) -> Result<PathBuf> {
+    let repo = git2::Repository::discover(repo_path)
+        .context("Not in a git repository")?;
+
+    let hooks_dir = repo.path().join("hooks");
+    let hook_path = hooks_dir.join("commit-msg");
+
+    if hook_path.exists() {
+        return Err(anyhow!("A commit-msg hook already exists at {}", hook_path.display()));
+    }
+
+    fs::create_dir_all(&hooks_dir)
+        .context("Failed to create hooks directory")?;
+    fs::write(&hook_path, "#!/bin/sh\n# Installed by synaptic: reject messages that would lose SVCMS memories\nexec synaptic lint \"$1\"\n")
+        .context("Failed to write commit-msg hook")?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))
+            .context("Failed to make commit-msg hook executable")?;
+    }
+
+    Ok(hook_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint_valid_message() {
+        let message = "learned(api): rate limits reset per minute\n\nMemory: Rate limit resets at :00 of each minute";
+        assert!(lint_message(message, None).is_empty());
+    }
+
+    #[test]
+    fn test_lint_typo_in_type() {
+        let diagnostics = lint_message("lerned(api): rate limits reset per minute", None);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(has_failures(&diagnostics, false));
+        assert_eq!(diagnostics[0].to_string(), "1:1: error: unknown type `lerned`, did you mean `learned`");
+    }
+
+    #[test]
+    fn test_lint_memory_best_practices() {
+        let long_memory = "word ".repeat(50);
+        let message = format!(
+            "learned(api): rate limits reset per minute\n\nMemory: Rate limits reset per minute\nMemory: {}",
+            long_memory
+        );
+        let diagnostics = lint_message(&message, None);
+
+        let kinds: Vec<&DiagnosticKind> = diagnostics.iter().map(|d| &d.kind).collect();
+        assert_eq!(kinds, vec![&DiagnosticKind::MemoryRepeatsSummary, &DiagnosticKind::MemoryTooLong { length: 249 }]);
+        assert!(!has_failures(&diagnostics, false));
+        assert!(has_failures(&diagnostics, true));
+
+        let diagnostics = lint_message("decision(db): use postgres", None);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::MissingMemory { commit_type: "decision".to_string() });
+        assert_eq!(diagnostics[0].span, Span::new(1, 1, 8));
+    }
+
+    #[test]
+    fn test_lint_hook_input() {
+        let message = "fix(api): handle empty body\n# Please enter the commit message\n# ------------------------ >8 ------------------------\ndiff --git a/x b/x";
+        assert_eq!(strip_comments(message, "#"), "fix(api): handle empty body");
+        assert!(lint_message(&strip_comments(message, "#"), None).is_empty());
+
+        assert!(lint_message("Merge branch 'main' into feature", None).is_empty());
+        assert!(lint_message("fixup! feat(api): add endpoint", None).is_empty());
+    }
+
+    #[test]
+    fn test_strip_comments_uses_comment_char() {
+        let message = "fix(api): handle empty body\n\n#123 stays\n; Please enter the commit message\n; ------------------------ >8 ------------------------\ndiff";
+        assert_eq!(strip_comments(message, ";"), "fix(api): handle empty body\n\n#123 stays");
+        // With auto, `#123` isn't a comment line, so `;` is the comment character
+        assert_eq!(strip_comments(message, "auto"), "fix(api): handle empty body\n\n#123 stays");
+        assert_eq!(strip_comments("feat: x\n// note", "//"), "feat: x");
+    }
+}
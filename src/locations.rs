@@ -0,0 +1,294 @@
+//! Routing of scopes to CLAUDE.md files from `[locations]` and scope groups
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use crate::config::{CommitTypeScopesConfig, ScopeConfig};
+
+/// Which rule placed a memory in its CLAUDE.md
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Placement {
+    /// A `Location:` footer on the memory or commit
+    Explicit,
+    /// A `[locations]` entry, by key
+    Config(String),
+    /// The commit's changed files, under the named location strategy
+    ChangedFiles(&'static str),
+    /// A configured scope group (`modules`, `project_wide`, ...)
+    ScopeGroup(&'static str),
+    /// Inferred from an unconfigured scope
+    Scope,
+    /// No other rule applied
+    Root,
+}
+
+impl std::fmt::Display for Placement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Placement::Explicit => write!(f, "Location footer"),
+            Placement::Config(key) => write!(f, "[locations] {}", key),
+            Placement::ChangedFiles(strategy) => write!(f, "changed files ({})", strategy),
+            Placement::ScopeGroup(group) => write!(f, "scope group {}", group),
+            Placement::Scope => write!(f, "scope"),
+            Placement::Root => write!(f, "root"),
+        }
+    }
+}
+
+/// One `[locations]` entry
+#[derive(Debug, Clone, PartialEq)]
+struct LocationRule {
+    key: String,
+    commit_type: Option<String>,
+    scope: String,
+    target: String,
+}
+
+impl LocationRule {
+    /// Parse `scope-pattern` or `type(scope-pattern)`
+    fn parse(key: &str, target: &str) -> Self {
+        let (commit_type, scope) = match key.split_once('(') {
+            Some((commit_type, rest)) if rest.ends_with(')') => {
+                (Some(commit_type.trim().to_string()), rest[..rest.len() - 1].trim().to_string())
+            }
+            _ => (None, key.trim().to_string()),
+        };
+        LocationRule { key: key.to_string(), commit_type, scope, target: target.to_string() }
+    }
+
+    fn matches(&self, commit_type: &str, scope: Option<&str>) -> bool {
+        self.commit_type.as_ref().is_none_or(|t| t == commit_type) &&
+            glob_match(&self.scope, scope.unwrap_or_default())
+    }
+
+    /// Target CLAUDE.md relative to the project root
+    ///
+    /// `{scope}` is replaced by the commit's scope.
+    fn target(&self, scope: Option<&str>) -> PathBuf {
+        target_path(&self.target.replace("{scope}", scope.unwrap_or_default()))
+    }
+}
+
+/// A configured target as a CLAUDE.md path; a directory gets `CLAUDE.md` appended
+fn target_path(target: &str) -> PathBuf {
+    let target = target.trim_end_matches('/');
+    if target.ends_with(".md") {
+        PathBuf::from(target)
+    } else {
+        PathBuf::from(target).join("CLAUDE.md")
+    }
+}
+
+/// Scope-to-file routing from the `[locations]` section
+///
+/// Keys are scope patterns (`auth`, `api/*`) or per-type overrides
+/// (`docs(*)`, `fix(api/*)`). The most specific matching rule wins:
+/// per-type before scope-only, exact before glob, longer before shorter.
+#[derive(Debug, Clone, Default)]
+pub struct LocationMap {
+    rules: Vec<LocationRule>,
+}
+
+impl LocationMap {
+    pub fn new(locations: &HashMap<String, String>) -> Self {
+        let mut rules: Vec<LocationRule> = locations.iter()
+            .map(|(key, target)| LocationRule::parse(key, target))
+            .collect();
+        rules.sort_by_key(|rule| (
+            rule.commit_type.is_none(),
+            rule.scope.contains(['*', '?']),
+            std::cmp::Reverse(rule.scope.chars().filter(|c| !matches!(c, '*' | '?')).count()),
+            rule.key.clone(),
+        ));
+        LocationMap { rules }
+    }
+
+    /// Find the rule for a commit, returning its key and target path
+    pub fn lookup(&self, commit_type: &str, scope: Option<&str>) -> Option<(&str, PathBuf)> {
+        self.rules.iter()
+            .find(|rule| rule.matches(commit_type, scope))
+            .map(|rule| (rule.key.as_str(), rule.target(scope)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+/// CLAUDE.md of an unconfigured scope: `src/<scope>`, or the scope itself when it is a path
+pub fn infer_scope_location(scope: &str) -> PathBuf {
+    if scope.contains('/') {
+        PathBuf::from(scope).join("CLAUDE.md")
+    } else {
+        PathBuf::from("src").join(scope).join("CLAUDE.md")
+    }
+}
+
+/// Scopes that go to the root CLAUDE.md unless a scope group says otherwise
+pub const DEFAULT_PROJECT_WIDE_SCOPES: &[&str] = &[
+    "global", "project", "build", "ci", "chore",
+    "docs", "test", "tests", "testing", "cleanup",
+    "workflow", "development", "architecture",
+    "authors", "roadmap", "memory", "mvp", "milestone",
+];
+
+/// Scope routing from the `[commit_types.scopes]` groups
+///
+/// `project_wide` scopes go to the root CLAUDE.md, `modules` to their
+/// directory, and `cross_cutting` and `tooling` to their group's target.
+/// A scope's own `path` overrides its group. The scopes in
+/// `DEFAULT_PROJECT_WIDE_SCOPES` are project-wide unless configured.
+#[derive(Debug, Clone)]
+pub struct ScopeGroups {
+    scopes: HashMap<String, (&'static str, Option<String>)>,
+    cross_cutting_target: Option<String>,
+    tooling_target: Option<String>,
+}
+
+impl Default for ScopeGroups {
+    fn default() -> Self {
+        ScopeGroups {
+            scopes: DEFAULT_PROJECT_WIDE_SCOPES.iter()
+                .map(|scope| (scope.to_string(), ("project_wide", None)))
+                .collect(),
+            cross_cutting_target: None,
+            tooling_target: None,
+        }
+    }
+}
+
+impl ScopeGroups {
+    pub fn from_config(config: &CommitTypeScopesConfig) -> Self {
+        let mut scopes = ScopeGroups::default().scopes;
+        let groups: [(&'static str, &Option<HashMap<String, ScopeConfig>>); 4] = [
+            ("project_wide", &config.project_wide),
+            ("tooling", &config.tooling),
+            ("cross_cutting", &config.cross_cutting),
+            ("modules", &config.modules),
+        ];
+        // Later groups win when a scope is listed twice, matching validation order
+        for (group, members) in groups {
+            for (scope, scope_config) in members.iter().flatten() {
+                scopes.insert(scope.clone(), (group, scope_config.path.clone()));
+            }
+        }
+
+        ScopeGroups {
+            scopes,
+            cross_cutting_target: config.cross_cutting_target.clone(),
+            tooling_target: config.tooling_target.clone(),
+        }
+    }
+
+    /// CLAUDE.md for a configured scope, relative to the project root
+    pub fn locate(&self, scope: &str) -> Option<(PathBuf, Placement)> {
+        let (group, path) = self.scopes.get(scope)?;
+        let target = match (path, *group) {
+            (Some(path), _) => target_path(path),
+            (None, "modules") => infer_scope_location(scope),
+            (None, "cross_cutting") => target_path(self.cross_cutting_target.as_deref().unwrap_or("CLAUDE.md")),
+            (None, "tooling") => target_path(self.tooling_target.as_deref().unwrap_or("CLAUDE.md")),
+            (None, _) => PathBuf::from("CLAUDE.md"),
+        };
+        Some((target, Placement::ScopeGroup(group)))
+    }
+}
+
+/// Match `text` against a pattern where `*` is any run of characters and `?` one character
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("api/*", "api/v2"));
+        assert!(glob_match("api/*", "api/"));
+        assert!(!glob_match("api/*", "api"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*-db", "users-db"));
+        assert!(glob_match("a?c*e", "abcde"));
+        assert!(!glob_match("auth", "oauth"));
+    }
+
+    #[test]
+    fn test_scope_groups() {
+        let config: CommitTypeScopesConfig = toml::from_str(r#"
+tooling_target = "tools"
+
+[modules]
+auth = { categories = ["all"], custom_types = [] }
+billing = { categories = ["all"], custom_types = [], path = "services/billing" }
+
+[cross_cutting]
+security = { categories = ["all"], custom_types = [] }
+
+[tooling]
+docker = { categories = ["all"], custom_types = [] }
+
+[project_wide]
+roadmap = { categories = ["all"], custom_types = [] }
+"#).unwrap();
+        let groups = ScopeGroups::from_config(&config);
+
+        assert_eq!(groups.locate("auth"), Some((PathBuf::from("src/auth/CLAUDE.md"), Placement::ScopeGroup("modules"))));
+        assert_eq!(groups.locate("billing"), Some((PathBuf::from("services/billing/CLAUDE.md"), Placement::ScopeGroup("modules"))));
+        assert_eq!(groups.locate("security"), Some((PathBuf::from("CLAUDE.md"), Placement::ScopeGroup("cross_cutting"))));
+        assert_eq!(groups.locate("docker"), Some((PathBuf::from("tools/CLAUDE.md"), Placement::ScopeGroup("tooling"))));
+        assert_eq!(groups.locate("roadmap"), Some((PathBuf::from("CLAUDE.md"), Placement::ScopeGroup("project_wide"))));
+        assert_eq!(groups.locate("parser"), None);
+        // Default project-wide scopes still apply alongside the configured groups
+        assert_eq!(groups.locate("global"), Some((PathBuf::from("CLAUDE.md"), Placement::ScopeGroup("project_wide"))));
+    }
+
+    #[test]
+    fn test_default_scope_groups() {
+        let groups = ScopeGroups::default();
+        assert_eq!(groups.locate("docs"), Some((PathBuf::from("CLAUDE.md"), Placement::ScopeGroup("project_wide"))));
+        assert_eq!(groups.locate("ci"), Some((PathBuf::from("CLAUDE.md"), Placement::ScopeGroup("project_wide"))));
+        assert_eq!(groups.locate("auth"), None);
+    }
+
+    #[test]
+    fn test_location_map_precedence() {
+        let mut locations = HashMap::new();
+        locations.insert("auth".to_string(), "src/authentication/CLAUDE.md".to_string());
+        locations.insert("api/*".to_string(), "services/api".to_string());
+        locations.insert("api/v2".to_string(), "services/api/v2/CLAUDE.md".to_string());
+        locations.insert("docs(*)".to_string(), "docs/CLAUDE.md".to_string());
+        locations.insert("plugins/*".to_string(), "{scope}/".to_string());
+        let map = LocationMap::new(&locations);
+
+        assert_eq!(map.lookup("feat", Some("auth")), Some(("auth", PathBuf::from("src/authentication/CLAUDE.md"))));
+        assert_eq!(map.lookup("fix", Some("api/v1")), Some(("api/*", PathBuf::from("services/api/CLAUDE.md"))));
+        assert_eq!(map.lookup("fix", Some("api/v2")), Some(("api/v2", PathBuf::from("services/api/v2/CLAUDE.md"))));
+        assert_eq!(map.lookup("docs", Some("auth")), Some(("docs(*)", PathBuf::from("docs/CLAUDE.md"))));
+        assert_eq!(map.lookup("docs", None), Some(("docs(*)", PathBuf::from("docs/CLAUDE.md"))));
+        assert_eq!(map.lookup("feat", Some("plugins/git")), Some(("plugins/*", PathBuf::from("plugins/git/CLAUDE.md"))));
+        assert_eq!(map.lookup("feat", Some("parser")), None);
+        assert_eq!(map.lookup("feat", None), None);
+    }
+}
@@ -1,6 +1,12 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use synaptic::{git, memory, config::SynapticConfig, obsidian::ObsidianManager};
+use synaptic::{git, lint, memory, config::SynapticConfig, obsidian::ObsidianManager};
+use synaptic::dates::DateRange;
+use synaptic::source::{self, CommitSource, RepositorySource};
+use synaptic::state::{self, SyncCursor, SyncState};
+use synaptic::trust::{self, TrustPolicy, UntrustedAction};
+use synaptic::cleanup::{self, CleanupMode, CleanupPolicy, CleanupReason};
+use synaptic::validate::{self, ValidationOptions};
 
 #[derive(Parser)]
 #[command(name = "synaptic")]
@@ -14,21 +20,129 @@ struct Cli {
 enum Commands {
     /// Sync commit memories to CLAUDE.md files
     Sync {
-        /// Number of commits to process
-        #[arg(short, long, default_value = "100")]
-        depth: usize,
+        /// Number of commits to process (default: 100 with --range or --all;
+        /// the whole branch on its first sync, after a rebase or with --full)
+        #[arg(short, long)]
+        depth: Option<usize>,
         
         /// Preview changes without writing files
         #[arg(long)]
         dry_run: bool,
         
-        /// Process commits since this date (YYYY-MM-DD)
+        /// Process commits since this date (YYYY-MM-DD, "2 weeks ago", "last monday")
         #[arg(long)]
         since: Option<String>,
+        
+        /// Process commits up to this date (same formats as --since; a plain date includes its whole day)
+        #[arg(long)]
+        until: Option<String>,
+        
+        /// Revisions to walk instead of HEAD (e.g. main, v1.2.0..HEAD, origin/main...feature)
+        #[arg(long, num_args = 1..)]
+        range: Vec<String>,
+        
+        /// Walk every ref, like `git log --all`
+        #[arg(long)]
+        all: bool,
+        
+        /// Ignore the saved sync state and walk the whole depth window again
+        #[arg(long)]
+        full: bool,
+        
+        /// Read commits without a repository: an mbox or format-patch directory,
+        /// a git bundle, or `-` for `git log -z --format=%H%x00%B` / NDJSON on stdin
+        #[arg(long, conflicts_with_all = ["since", "until", "range", "all", "full"])]
+        from: Option<String>,
     },
     
     /// Show statistics about SVCMS commits
-    Stats,
+    Stats {
+        /// Revisions to walk instead of HEAD (e.g. main, v1.2.0..HEAD, origin/main...feature)
+        #[arg(long, num_args = 1..)]
+        range: Vec<String>,
+        
+        /// Walk every ref, like `git log --all`
+        #[arg(long)]
+        all: bool,
+        
+        /// Include changed files and line counts (diffs every commit)
+        #[arg(long)]
+        files: bool,
+        
+        /// Read commits without a repository (same sources as `sync --from`)
+        #[arg(long, conflicts_with_all = ["range", "all"])]
+        from: Option<String>,
+    },
+    
+    /// Attach memories to an existing commit with a git note
+    Annotate {
+        /// Commit to annotate
+        rev: String,
+        
+        /// Memory to attach (repeat for several)
+        #[arg(short, long, required = true)]
+        memory: Vec<String>,
+        
+        /// CLAUDE.md the memories go to
+        #[arg(short, long)]
+        location: Option<String>,
+        
+        /// Comma-separated tags
+        #[arg(short, long)]
+        tags: Option<String>,
+        
+        /// Type for commits that aren't SVCMS-formatted
+        #[arg(long = "type", default_value = "learned")]
+        commit_type: String,
+        
+        /// Scope for commits that aren't SVCMS-formatted
+        #[arg(long)]
+        scope: Option<String>,
+    },
+    
+    /// Move reverted and old memories out of CLAUDE.md files
+    Cleanup {
+        /// Preview without writing files
+        #[arg(long)]
+        dry_run: bool,
+        
+        /// Delete memories instead of archiving them
+        #[arg(long)]
+        delete: bool,
+        
+        /// Clean up memories older than this many days (overrides retention_days)
+        #[arg(long)]
+        retention_days: Option<u32>,
+    },
+    
+    /// Report synced memories that have gone stale
+    Validate {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+        
+        /// Exit non-zero when any memory is stale (also set by report_stale_memories)
+        #[arg(long)]
+        strict: bool,
+    },
+    
+    /// Validate commit messages against SVCMS (usable as a commit-msg hook)
+    Lint {
+        /// Commit message file to check (reads stdin when omitted or "-")
+        file: Option<std::path::PathBuf>,
+        
+        /// Check every commit in a revision range instead (e.g. origin/main..HEAD)
+        #[arg(long, conflicts_with = "file")]
+        range: Option<String>,
+        
+        /// Treat warnings as errors
+        #[arg(long)]
+        strict: bool,
+        
+        /// Install `synaptic lint` as this repository's commit-msg hook
+        #[arg(long, conflicts_with_all = ["file", "range"])]
+        install_hook: bool,
+    },
     
     /// Initialize Synaptic in the current project
     Init {
@@ -56,11 +170,72 @@ enum VaultCommands {
     Init,
 }
 
+/// Notes ref to read and write SVCMS trailers, from config or the default
+fn notes_ref(config: &SynapticConfig) -> String {
+    config.notes_ref()
+        .unwrap_or(git::DEFAULT_NOTES_REF)
+        .to_string()
+}
+
+/// The `[trust]` policy, or an open one without a `[trust]` section
+fn trust_policy(config: &SynapticConfig, repo_path: &str) -> TrustPolicy {
+    config.trust()
+        .map(|trust| TrustPolicy::from_config(trust, std::path::Path::new(repo_path)))
+        .unwrap_or_default()
+}
+
+/// Notes to read, if any: anyone who can push the notes ref could attach
+/// memories to a trusted commit, so notes are ignored under a trust policy
+fn trusted_notes_ref(config: &SynapticConfig, trust: &TrustPolicy) -> Option<String> {
+    (!trust.is_enforced()).then(|| notes_ref(config))
+}
+
+/// Clean up the project's CLAUDE.md files and remember what was taken out
+fn run_cleanup(repo_path: &str, policy: &CleanupPolicy, dry_run: bool) -> Result<()> {
+    let cleaned = cleanup::cleanup_project(std::path::Path::new(repo_path), policy, dry_run)?;
+    if cleaned.is_empty() {
+        println!("🧹 Nothing to clean up");
+        return Ok(());
+    }
+    
+    let mut files: Vec<&std::path::Path> = cleaned.iter().map(|m| m.path.as_path()).collect();
+    files.dedup();
+    for path in &files {
+        let memories: Vec<_> = cleaned.iter().filter(|m| m.path == *path).collect();
+        let reverted = memories.iter().filter(|m| m.reason == CleanupReason::Reverted).count();
+        println!("   {} ({} expired, {} reverted)", path.display(), memories.len() - reverted, reverted);
+    }
+    
+    let verb = match (dry_run, policy.mode) {
+        (true, CleanupMode::Archive) => "Would archive",
+        (true, CleanupMode::Delete) => "Would delete",
+        (false, CleanupMode::Archive) => "Archived",
+        (false, CleanupMode::Delete) => "Deleted",
+    };
+    println!("🧹 {} {} memories from {} files", verb, cleaned.len(), files.len());
+    
+    // Syncs must not write them back (outside a repository there are no syncs)
+    if let Some(mut sync_state) = SyncState::load(std::path::Path::new(repo_path)).ok().filter(|_| !dry_run) {
+        sync_state.retired.extend(cleaned.into_iter().map(|m| m.id));
+        sync_state.save(std::path::Path::new(repo_path))?;
+    }
+    
+    Ok(())
+}
+
+/// Expand a leading `~/` to the user's home directory
+fn expand_tilde(path: &str) -> std::path::PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => std::path::PathBuf::from(path),
+    }
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
     
     match cli.command {
-        Commands::Sync { depth, dry_run, since } => {
+        Commands::Sync { depth, dry_run, since, until, range, all, full, from } => {
             println!("🧠 Synaptic Memory Sync");
             
             // Get the current directory as the repo path
@@ -69,81 +244,184 @@ fn main() -> Result<()> {
                 .ok_or_else(|| anyhow::anyhow!("Invalid current directory"))?
                 .to_string();
             
+            // Plain syncs of HEAD resume where the last one stopped
+            let incremental = since.is_none() && until.is_none() && range.is_empty() && !all && from.is_none();
+            let mut sync_state = if incremental {
+                SyncState::load(std::path::Path::new(&repo_path))?
+            } else {
+                SyncState::default()
+            };
+            let head = if incremental {
+                state::current_head(std::path::Path::new(&repo_path)).ok()
+            } else {
+                None
+            };
+            let cursor = match &head {
+                Some(_) if full => Some(SyncCursor::Fresh),
+                Some(head) => Some(sync_state.cursor(std::path::Path::new(&repo_path), head)?),
+                None => None,
+            };
+            let depth = match &cursor {
+                Some(cursor) => cursor.depth(depth),
+                None => depth.unwrap_or(state::DEFAULT_DEPTH),
+            };
+            
+            // A broken config must not quietly drop the trust policy
+            let config = SynapticConfig::load()?;
+            let merge_policy = git::MergePolicy::from_config(&config);
+            let trust = trust_policy(&config, &repo_path);
+            
             // Get commits based on parameters
-            let commits = if let Some(since_date) = since {
-                println!("Processing commits since {}...", since_date);
-                git::get_svcms_commits_since(&repo_path, &since_date)?
+            let revisions = git::Revisions::new(range, all)
+                .with_merge_policy(merge_policy)
+                .with_notes(trusted_notes_ref(&config, &trust));
+            let validator = config.commit_type_validator();
+            let mut commits = if let Some(from) = &from {
+                let source = source::open_source(from)?;
+                println!("Processing {}...", source.describe());
+                source.commits(validator.as_ref())?
+            } else if since.is_some() || until.is_some() {
+                let dates = DateRange::parse(since.as_deref(), until.as_deref())?;
+                match (&since, &until) {
+                    (Some(since), Some(until)) => println!("Processing commits from {} until {}...", since, until),
+                    (Some(since), None) => println!("Processing commits since {}...", since),
+                    _ => println!("Processing commits until {}...", until.as_deref().unwrap_or_default()),
+                }
+                git::get_svcms_commits_between(&repo_path, &revisions, &dates)?
+            } else if let Some(SyncCursor::Resume(new_commits)) = &cursor {
+                println!("Processing commits since the last sync...");
+                let new_commits = new_commits.clone()
+                    .with_merge_policy(merge_policy)
+                    .with_notes(revisions.notes.clone());
+                let mut commits = git::get_svcms_commits(&repo_path, &new_commits, usize::MAX)?;
+                
+                // Notes may have been added to commits synced before, anywhere on the branch
+                for annotated in git::get_annotated_commits(&repo_path, &revisions)? {
+                    if !commits.iter().any(|c| c.sha == annotated.sha && c.summary == annotated.summary) {
+                        commits.push(annotated);
+                    }
+                }
+                commits
             } else {
-                println!("Processing {} commits...", depth);
-                git::get_svcms_commits(&repo_path, depth)?
+                if let Some(SyncCursor::Reset { last_synced }) = &cursor {
+                    println!("⚠️  Last synced commit {} is no longer in the branch history (force-push or rebase?)", &last_synced[..7]);
+                }
+                let source = RepositorySource::new(repo_path.clone(), revisions, depth);
+                println!("Processing {}...", source.describe());
+                source.commits(validator.as_ref())?
             };
             
             if dry_run {
                 println!("(dry run - no files will be modified)");
             }
             
-            // Try to load config for Obsidian integration
-            let config = SynapticConfig::load().ok();
-            
-            if let Some(config) = &config {
-                if let Some(obsidian_config) = config.obsidian() {
-                    // Get vault path, check if it exists
-                    if let Some(vault_path_str) = &obsidian_config.vault_path {
-                        // Expand tilde in vault path
-                        let vault_path = if vault_path_str.starts_with("~/") {
-                            if let Some(home) = dirs::home_dir() {
-                                home.join(&vault_path_str[2..])
-                            } else {
-                                std::path::PathBuf::from(vault_path_str)
-                            }
-                        } else {
-                            std::path::PathBuf::from(vault_path_str)
-                        };
-                        
-                        let synaptic_folder = config.synaptic_folder();
-                        
-                        // Get project name from config
-                        let project_name = config.obsidian()
-                            .and_then(|o| o.project_name.as_ref())
-                            .map(|s| s.as_str())
-                            .unwrap_or_else(|| {
-                                // Fallback to repo folder name
-                                std::path::Path::new(&repo_path)
-                                    .file_name()
-                                    .and_then(|n| n.to_str())
-                                    .unwrap_or("unknown-project")
-                            });
-                        
-                        // Try Obsidian integration
-                        match ObsidianManager::new(vault_path, synaptic_folder) {
-                            Ok(obsidian_manager) => {
-                                // Sync with Obsidian integration
-                                memory::sync_memories_with_obsidian(commits, &repo_path, dry_run, &obsidian_manager, project_name)?;
-                            }
-                            Err(e) => {
-                                println!("⚠️  Obsidian integration unavailable: {}", e);
-                                println!("🔄 Falling back to CLAUDE.md-only sync...");
-                                memory::sync_memories(commits, &repo_path, dry_run)?;
-                            }
+            // Without a repository there are no diffs to compare
+            if from.is_none() && config.auto_deduplicate() {
+                let merged = git::deduplicate_commits(&repo_path, &mut commits)?;
+                if merged > 0 {
+                    println!("Merged {} cherry-picked or rebased copies", merged);
+                }
+            }
+            
+            if config.promote_breaking_changes() {
+                let promoted = memory::promote_breaking_changes(&mut commits);
+                if promoted > 0 {
+                    println!("Promoted {} breaking changes to memories", promoted);
+                }
+            }
+            
+            let (mut commits, untrusted) = trust::partition_commits(&trust, from.is_none().then_some(repo_path.as_str()), commits)?;
+            if !untrusted.is_empty() {
+                match trust.untrusted {
+                    UntrustedAction::Skip => {
+                        println!("⚠️  Skipped {} untrusted commits with memories", untrusted.len());
+                    }
+                    UntrustedAction::Quarantine => {
+                        let added = trust::quarantine(&trust.quarantine_file, &untrusted, dry_run)?;
+                        println!("⚠️  Quarantined {} untrusted commits with memories ({} new) in {}",
+                            untrusted.len(), added, trust.quarantine_file.display());
+                    }
+                }
+            }
+            
+            // Validation mustn't look for exported commits in the repository
+            if from.is_some() && !dry_run {
+                if let Ok(mut state) = SyncState::load(std::path::Path::new(&repo_path)) {
+                    state.imported.extend(commits.iter()
+                        .filter(|c| !c.memory.is_empty())
+                        .map(|c| c.full_sha.clone().unwrap_or_else(|| c.sha.clone())));
+                    state.save(std::path::Path::new(&repo_path))?;
+                }
+            }
+            
+            let mut sync_options = memory::SyncOptions::from_config(&config);
+            sync_options.retired = SyncState::load(std::path::Path::new(&repo_path))
+                .map(|state| state.retired)
+                .unwrap_or_default();
+            
+            let wants_changed_files = config.changed_files() || sync_options.location_strategy.needs_changed_files();
+            if from.is_none() && wants_changed_files {
+                git::attach_changed_files(&repo_path, &mut commits)?;
+            }
+            
+            if let Some(obsidian_config) = config.obsidian() {
+                // Get vault path, check if it exists
+                if let Some(vault_path_str) = &obsidian_config.vault_path {
+                    // Expand tilde in vault path
+                    let vault_path = expand_tilde(vault_path_str);
+                    
+                    let synaptic_folder = config.synaptic_folder();
+                    
+                    // Get project name from config
+                    let project_name = config.obsidian()
+                        .and_then(|o| o.project_name.as_ref())
+                        .map(|s| s.as_str())
+                        .unwrap_or_else(|| {
+                            // Fallback to repo folder name
+                            std::path::Path::new(&repo_path)
+                                .file_name()
+                                .and_then(|n| n.to_str())
+                                .unwrap_or("unknown-project")
+                        });
+                    
+                    // Try Obsidian integration
+                    match ObsidianManager::new(vault_path, synaptic_folder) {
+                        Ok(obsidian_manager) => {
+                            let obsidian_manager = obsidian_manager
+                                .with_issue_url_template(sync_options.issue_url_template.clone());
+                            // Sync with Obsidian integration
+                            memory::sync_memories_with_obsidian(commits, &repo_path, dry_run, &sync_options, &obsidian_manager, project_name)?;
+                        }
+                        Err(e) => {
+                            println!("⚠️  Obsidian integration unavailable: {}", e);
+                            println!("🔄 Falling back to CLAUDE.md-only sync...");
+                            memory::sync_memories(commits, &repo_path, dry_run, &sync_options)?;
                         }
-                    } else {
-                        // No vault path configured
-                        println!("⚠️  No vault_path in [obsidian] config");
-                        println!("🔄 Using CLAUDE.md-only sync...");
-                        memory::sync_memories(commits, &repo_path, dry_run)?;
                     }
                 } else {
-                    // No Obsidian config, use regular sync
-                    memory::sync_memories(commits, &repo_path, dry_run)?;
+                    // No vault path configured
+                    println!("⚠️  No vault_path in [obsidian] config");
+                    println!("🔄 Using CLAUDE.md-only sync...");
+                    memory::sync_memories(commits, &repo_path, dry_run, &sync_options)?;
                 }
             } else {
-                // No config file, use regular sync
-                memory::sync_memories(commits, &repo_path, dry_run)?;
+                // No Obsidian config, use regular sync
+                memory::sync_memories(commits, &repo_path, dry_run, &sync_options)?;
+            }
+            
+            if let Some(head) = head.filter(|_| !dry_run) {
+                sync_state.record(&head);
+                sync_state.save(std::path::Path::new(&repo_path))?;
+            }
+            
+            if let Some(cleanup_config) = config.cleanup().filter(|_| config.auto_cleanup_on_sync()) {
+                println!();
+                run_cleanup(&repo_path, &CleanupPolicy::from_config(cleanup_config), dry_run)?;
             }
             
             Ok(())
         }
-        Commands::Stats => {
+        Commands::Stats { range, all, files, from } => {
             // Get the current directory as the repo path
             let repo_path = std::env::current_dir()?
                 .to_str()
@@ -151,11 +429,142 @@ fn main() -> Result<()> {
                 .to_string();
             
             // Get all commits (up to 1000)
-            let commits = git::get_svcms_commits(&repo_path, 1000)?;
+            let config = SynapticConfig::load()?;
+            let merge_policy = git::MergePolicy::from_config(&config);
+            let trust = trust_policy(&config, &repo_path);
+            let revisions = git::Revisions::new(range, all)
+                .with_merge_policy(merge_policy)
+                .with_notes(trusted_notes_ref(&config, &trust));
+            
+            let validator = config.commit_type_validator();
+            let source: Box<dyn CommitSource> = match &from {
+                Some(from) => source::open_source(from)?,
+                None => Box::new(RepositorySource::new(repo_path.clone(), revisions, 1000)),
+            };
+            let mut commits = source.commits(validator.as_ref())?;
+            if from.is_none() && config.auto_deduplicate() {
+                git::deduplicate_commits(&repo_path, &mut commits)?;
+            }
+            if files && from.is_none() {
+                git::attach_changed_files(&repo_path, &mut commits)?;
+            }
             
             // Print statistics
             git::print_commit_stats(&commits);
             
+            let (_, untrusted) = trust::partition_commits(&trust, from.is_none().then_some(repo_path.as_str()), commits)?;
+            trust::print_trust_stats(&untrusted);
+            
+            Ok(())
+        }
+        Commands::Annotate { rev, memory, location, tags, commit_type, scope } => {
+            let repo_path = std::env::current_dir()?
+                .to_str()
+                .ok_or_else(|| anyhow::anyhow!("Invalid current directory"))?
+                .to_string();
+            let config = SynapticConfig::load()?;
+            let notes_ref = notes_ref(&config);
+            
+            let trailers = git::annotation_trailers(&memory, location.as_deref(), tags.as_deref());
+            
+            let header = match scope {
+                Some(scope) => format!("{}({})", commit_type, scope),
+                None => commit_type,
+            };
+            
+            let sha = git::annotate_commit(&repo_path, &rev, &notes_ref, &header, &trailers)?;
+            let noun = if memory.len() == 1 { "memory" } else { "memories" };
+            println!("✅ Added {} {} to {} in {}", memory.len(), noun, sha, notes_ref);
+            println!("   Share it with: git push origin {}", notes_ref);
+            
+            Ok(())
+        }
+        Commands::Cleanup { dry_run, delete, retention_days } => {
+            let repo_path = std::env::current_dir()?
+                .to_str()
+                .ok_or_else(|| anyhow::anyhow!("Invalid current directory"))?
+                .to_string();
+            let config = SynapticConfig::load()?;
+            
+            let mut policy = config.cleanup()
+                .map(CleanupPolicy::from_config)
+                .unwrap_or_default();
+            if delete {
+                policy.mode = CleanupMode::Delete;
+            }
+            if retention_days.is_some() {
+                policy.retention_days = retention_days;
+            }
+            
+            if dry_run {
+                println!("(dry run - no files will be modified)");
+            }
+            run_cleanup(&repo_path, &policy, dry_run)
+        }
+        Commands::Validate { json, strict } => {
+            // A broken config must not quietly turn the check off
+            let config = SynapticConfig::load()?;
+            let options = ValidationOptions::from_config(&config);
+            
+            let report = validate::validate_project(&std::env::current_dir()?, &options)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                validate::print_report(&report);
+            }
+            
+            let fail = strict || config.report_stale_memories();
+            if fail && !report.stale.is_empty() {
+                std::process::exit(1);
+            }
+            
+            Ok(())
+        }
+        Commands::Lint { file, range, strict, install_hook } => {
+            if install_hook {
+                let hook_path = lint::install_commit_msg_hook(&std::env::current_dir()?)?;
+                println!("✅ Installed commit-msg hook at {}", hook_path.display());
+                return Ok(());
+            }
+            
+            // A broken config must not fall back to the builtin types
+            let validator = SynapticConfig::load()?.commit_type_validator();
+            
+            // Collect (source, message) pairs to check
+            let messages = if let Some(range) = range {
+                let repo_path = std::env::current_dir()?
+                    .to_str()
+                    .ok_or_else(|| anyhow::anyhow!("Invalid current directory"))?
+                    .to_string();
+                git::get_commit_messages(&repo_path, &git::Revisions::new(vec![range], false))?
+            } else {
+                let message = match &file {
+                    Some(path) if path.as_os_str() != "-" => std::fs::read_to_string(path)
+                        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?,
+                    _ => std::io::read_to_string(std::io::stdin())?,
+                };
+                // A message being edited still has git's comments; committed ones don't
+                let message = lint::strip_comments(&message, &lint::comment_string(&std::env::current_dir()?));
+                let source = file
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_else(|| "<stdin>".to_string());
+                vec![(source, message)]
+            };
+            
+            let mut failed = 0;
+            for (source, message) in &messages {
+                let diagnostics = lint::lint_message(message, validator.as_ref());
+                lint::print_diagnostics(source, message, &diagnostics);
+                if lint::has_failures(&diagnostics, strict) {
+                    failed += 1;
+                }
+            }
+            
+            if failed > 0 {
+                eprintln!("\n❌ {} of {} commit messages failed SVCMS lint", failed, messages.len());
+                std::process::exit(1);
+            }
+            
             Ok(())
         }
         Commands::Init { global, project, project_name } => {
@@ -258,15 +667,7 @@ fn main() -> Result<()> {
                     if let Some(obsidian_config) = config.obsidian() {
                         if let Some(vault_path_str) = &obsidian_config.vault_path {
                             // Expand tilde in vault path
-                            let vault_path = if vault_path_str.starts_with("~/") {
-                                if let Some(home) = dirs::home_dir() {
-                                    home.join(&vault_path_str[2..])
-                                } else {
-                                    std::path::PathBuf::from(vault_path_str)
-                                }
-                            } else {
-                                std::path::PathBuf::from(vault_path_str)
-                            };
+                            let vault_path = expand_tilde(vault_path_str);
                         
                             let synaptic_folder = config.synaptic_folder();
                             
@@ -6,55 +6,244 @@ use std::io::{Read, Write};
 use std::collections::HashMap;
 use anyhow::{Result, Context};
 use colored::Colorize;
-use crate::SvcmsCommit;
-
-/// Determine the target CLAUDE.md file for a commit
-pub fn determine_memory_location(commit: &SvcmsCommit, project_root: &str) -> PathBuf {
-    if let Some(location) = &commit.location {
-        // Explicit location specified
-        PathBuf::from(project_root).join(location)
-    } else if let Some(scope) = &commit.scope {
-        // Infer from scope
-        match scope.as_str() {
-            // Project-wide scopes
-            "global" | "project" | "build" | "ci" | "chore" | 
-            "docs" | "test" | "tests" | "testing" | "cleanup" |
-            "workflow" | "development" | "architecture" |
-            "authors" | "roadmap" | "memory" | "mvp" | "milestone" => {
-                PathBuf::from(project_root).join("CLAUDE.md")
-            }
-            // Module-specific scopes (assume they're in src/)
-            _ => {
-                // Check if scope looks like a path (contains /)
-                if scope.contains('/') {
-                    PathBuf::from(project_root).join(format!("{}/CLAUDE.md", scope))
-                } else {
-                    // Default to src/ subdirectory
-                    PathBuf::from(project_root).join(format!("src/{}/CLAUDE.md", scope))
-                }
-            }
+use crate::{SvcmsCommit, MemoryEntry, Reference};
+use crate::config::SynapticConfig;
+use crate::locations::{self, LocationMap, Placement, ScopeGroups};
+
+/// What happens to the memories of reverted commits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Retraction {
+    /// Keep the memory struck through, noting the revert
+    #[default]
+    Strike,
+    /// Delete the memory
+    Remove,
+}
+
+/// How memories without a `Location:` find their CLAUDE.md
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LocationStrategy {
+    /// Infer from the commit scope (`src/<scope>/CLAUDE.md`)
+    #[default]
+    Scope,
+    /// Deepest existing directory containing all changed files
+    CommonDirectory,
+    /// Nearest CLAUDE.md at or above the changed files' common directory
+    NearestClaudeMd,
+}
+
+impl LocationStrategy {
+    /// Parse the `[sync] location_strategy` value
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "scope" => Some(LocationStrategy::Scope),
+            "common-directory" => Some(LocationStrategy::CommonDirectory),
+            "nearest-claude-md" => Some(LocationStrategy::NearestClaudeMd),
+            _ => None,
         }
-    } else {
-        // Default to project root
-        PathBuf::from(project_root).join("CLAUDE.md")
     }
+    
+    /// The `[sync] location_strategy` value
+    pub fn name(&self) -> &'static str {
+        match self {
+            LocationStrategy::Scope => "scope",
+            LocationStrategy::CommonDirectory => "common-directory",
+            LocationStrategy::NearestClaudeMd => "nearest-claude-md",
+        }
+    }
+    
+    /// Whether commits need their changed files extracted for this strategy
+    pub fn needs_changed_files(&self) -> bool {
+        *self != LocationStrategy::Scope
+    }
+}
+
+/// Settings that shape how memories are written and where they go
+#[derive(Debug, Clone, Default)]
+pub struct SyncOptions {
+    /// Issue tracker URL with a `{number}` placeholder
+    pub issue_url_template: Option<String>,
+    pub retraction: Retraction,
+    pub location_strategy: LocationStrategy,
+    /// `[locations]` rules, consulted before the location strategy
+    pub locations: LocationMap,
+    /// Configured scope groups, consulted before scope inference
+    pub scope_groups: ScopeGroups,
+    /// Hidden IDs of memories taken out by cleanup, which are never written again
+    pub retired: std::collections::BTreeSet<String>,
 }
 
-/// Group memories by their target file
-fn group_memories_by_file(commits: &[SvcmsCommit], project_root: &str) -> HashMap<PathBuf, Vec<Memory>> {
+impl SyncOptions {
+    /// Take sync options from the loaded configuration
+    pub fn from_config(config: &SynapticConfig) -> Self {
+        SyncOptions {
+            issue_url_template: config.issue_url_template(),
+            retraction: match config.reverted_memories() {
+                Some("remove") => Retraction::Remove,
+                _ => Retraction::Strike,
+            },
+            location_strategy: match config.location_strategy() {
+                Some(name) => LocationStrategy::from_name(name).unwrap_or_else(|| {
+                    eprintln!(
+                        "⚠️  Unknown location_strategy {:?}, using \"scope\" (expected \"scope\", \"common-directory\" or \"nearest-claude-md\")",
+                        name
+                    );
+                    LocationStrategy::Scope
+                }),
+                None => LocationStrategy::Scope,
+            },
+            locations: config.locations()
+                .map(LocationMap::new)
+                .unwrap_or_default(),
+            scope_groups: config.scopes()
+                .map(ScopeGroups::from_config)
+                .unwrap_or_default(),
+            retired: Default::default(),
+        }
+    }
+}
+
+/// Determine the target CLAUDE.md for a commit from its `Location:` or scope
+pub fn determine_memory_location(commit: &SvcmsCommit, project_root: &str, scope_groups: &ScopeGroups) -> PathBuf {
+    let options = SyncOptions { scope_groups: scope_groups.clone(), ..Default::default() };
+    place_memory(commit, None, project_root, &options).0
+}
+
+/// Determine the target CLAUDE.md for a commit under a location strategy
+///
+/// An explicit `Location:` always wins. File-based strategies fall back to
+/// scope inference when the commit's changed files aren't known.
+pub fn locate_memory(commit: &SvcmsCommit, project_root: &str, strategy: LocationStrategy) -> PathBuf {
+    let options = SyncOptions { location_strategy: strategy, ..Default::default() };
+    place_memory(commit, None, project_root, &options).0
+}
+
+/// Target CLAUDE.md for one memory of a commit, and the rule that chose it
+///
+/// Rules apply in order: the memory's or commit's `Location:`, a
+/// `[locations]` entry, the changed files under a file-based strategy, the
+/// scope's configured group, scope inference, and finally the root CLAUDE.md.
+/// An inferred scope directory that doesn't exist is replaced by its nearest
+/// existing parent, as for changed files, so sync never invents directories.
+pub fn place_memory(commit: &SvcmsCommit, entry: Option<&MemoryEntry>, project_root: &str, options: &SyncOptions) -> (PathBuf, Placement) {
+    let root = Path::new(project_root);
+    
+    if let Some(location) = entry.and_then(|e| e.location.as_ref()).or(commit.location.as_ref()) {
+        return (root.join(location), Placement::Explicit);
+    }
+    if let Some((key, target)) = options.locations.lookup(&commit.commit_type, commit.scope.as_deref()) {
+        return (root.join(target), Placement::Config(key.to_string()));
+    }
+    if let Some(path) = changed_files_location(commit, root, options.location_strategy) {
+        return (path, Placement::ChangedFiles(options.location_strategy.name()));
+    }
+    let Some(scope) = &commit.scope else {
+        return (root.join("CLAUDE.md"), Placement::Root);
+    };
+    match options.scope_groups.locate(scope) {
+        Some((target, placement)) => (root.join(target), placement),
+        None => {
+            let target = root.join(locations::infer_scope_location(scope));
+            let dir = target.parent().unwrap_or(root).to_path_buf();
+            (existing_location(dir, root, options.location_strategy), Placement::Scope)
+        }
+    }
+}
+
+/// CLAUDE.md chosen from the directories of a commit's changed files
+///
+/// Only ever picks directories that exist, so no CLAUDE.md is created in a
+/// directory the commit deleted.
+fn changed_files_location(commit: &SvcmsCommit, project_root: &Path, strategy: LocationStrategy) -> Option<PathBuf> {
+    if strategy == LocationStrategy::Scope {
+        return None;
+    }
+    let files = commit.changed_files.as_ref().filter(|files| !files.is_empty())?;
+    
+    // Deepest directory shared by every changed path
+    let mut common: Option<Vec<&str>> = None;
+    for file in files {
+        let dirs: Vec<&str> = file.path.split('/').collect();
+        let dirs = &dirs[..dirs.len() - 1];
+        common = Some(match common {
+            None => dirs.to_vec(),
+            Some(common) => common.iter()
+                .zip(dirs)
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| *a)
+                .collect(),
+        });
+    }
+    let mut dir = project_root.to_path_buf();
+    dir.extend(common.unwrap_or_default());
+    
+    Some(existing_location(dir, project_root, strategy))
+}
+
+/// CLAUDE.md in `dir` or the nearest parent that exists (or, for
+/// `nearest-claude-md`, already has a CLAUDE.md), stopping at the project root
+fn existing_location(mut dir: PathBuf, project_root: &Path, strategy: LocationStrategy) -> PathBuf {
+    while dir != project_root && dir.starts_with(project_root) {
+        let found = match strategy {
+            LocationStrategy::NearestClaudeMd => dir.join("CLAUDE.md").is_file(),
+            _ => dir.is_dir(),
+        };
+        if found {
+            break;
+        }
+        dir.pop();
+    }
+    
+    dir.join("CLAUDE.md")
+}
+
+/// Promote breaking changes without a `Memory:` footer to memories
+///
+/// The memory text is the `BREAKING CHANGE:` description when present,
+/// otherwise the commit summary.
+pub fn promote_breaking_changes(commits: &mut [SvcmsCommit]) -> usize {
+    let mut promoted = 0;
+    
+    for commit in commits.iter_mut().filter(|c| c.breaking && c.memory.is_empty()) {
+        commit.memory.push(MemoryEntry {
+            content: commit.breaking_change.clone().unwrap_or_else(|| commit.summary.clone()),
+            location: None,
+        });
+        promoted += 1;
+    }
+    
+    promoted
+}
+
+/// Group memories by their target file, leaving out reverted commits
+fn group_memories_by_file(commits: &[SvcmsCommit], project_root: &str, options: &SyncOptions) -> HashMap<PathBuf, Vec<Memory>> {
     let mut grouped = HashMap::new();
     
     for commit in commits {
-        if let Some(memory_text) = &commit.memory {
-            let location = determine_memory_location(commit, project_root);
+        let sha = commit.full_sha.as_deref().unwrap_or(&commit.sha);
+        // Squash pieces share a SHA, so memories are numbered by their place
+        // in the whole message, whichever pieces this sync sees
+        for (position, entry) in commit.memory.iter().enumerate() {
+            let id = format!("{}:{}", sha, commit.memory_offset + position);
+            if commit.reverted_by.is_some() || options.retired.contains(&id) {
+                continue;
+            }
+            
+            let (location, placement) = place_memory(commit, Some(entry), project_root, options);
             let memory = Memory {
-                content: memory_text.clone(),
+                id,
+                content: entry.content.clone(),
                 commit_sha: commit.sha.clone(),
                 commit_type: commit.commit_type.clone(),
                 scope: commit.scope.clone(),
                 summary: commit.summary.clone(),
+                breaking: commit.breaking,
                 timestamp: commit.timestamp,
                 tags: commit.tags.clone(),
+                refs: commit.refs.clone(),
+                authors: commit.author_names(),
+                duplicates: commit.duplicates.clone(),
+                placement,
             };
             
             grouped.entry(location).or_insert_with(Vec::new).push(memory);
@@ -64,20 +253,67 @@ fn group_memories_by_file(commits: &[SvcmsCommit], project_root: &str) -> HashMa
     grouped
 }
 
+/// A reverted commit whose memories must be retracted from a file
+#[derive(Debug, Clone, PartialEq)]
+struct RevertedMemory {
+    commit_sha: String,
+    reverted_by: String,
+}
+
+/// Group the memories of reverted commits by the file they were synced to
+fn group_retractions_by_file(commits: &[SvcmsCommit], project_root: &str, options: &SyncOptions) -> HashMap<PathBuf, Vec<RevertedMemory>> {
+    let mut grouped: HashMap<PathBuf, Vec<RevertedMemory>> = HashMap::new();
+    
+    for commit in commits {
+        let Some(reverted_by) = &commit.reverted_by else { continue };
+        for entry in &commit.memory {
+            let (location, _) = place_memory(commit, Some(entry), project_root, options);
+            let retractions = grouped.entry(location).or_default();
+            // Earlier syncs may have written the memory under any copy's SHA
+            for sha in std::iter::once(&commit.sha).chain(&commit.duplicates) {
+                let retraction = RevertedMemory {
+                    commit_sha: sha.clone(),
+                    reverted_by: reverted_by.clone(),
+                };
+                if !retractions.contains(&retraction) {
+                    retractions.push(retraction);
+                }
+            }
+        }
+    }
+    
+    grouped
+}
+
 /// Represents a memory to be synced
 #[derive(Debug, Clone)]
 struct Memory {
+    /// Hidden ID written with the entry: `<full sha>:<memory index>`
+    id: String,
     content: String,
     commit_sha: String,
     commit_type: String,
     scope: Option<String>,
     summary: String,
-    timestamp: chrono::DateTime<chrono::Utc>,
+    breaking: bool,
+    timestamp: chrono::DateTime<chrono::FixedOffset>,
     tags: Vec<String>,
+    refs: Vec<Reference>,
+    /// Author and co-author names
+    authors: Vec<String>,
+    /// Short SHAs of other copies of the commit
+    duplicates: Vec<String>,
+    /// Rule that chose the target file
+    placement: Placement,
 }
 
+
+
 /// Format a memory for inclusion in CLAUDE.md
-fn format_memory(memory: &Memory) -> String {
+///
+/// `root_prefix` leads from the CLAUDE.md directory back to the project
+/// root, so file references become working relative links.
+fn format_memory(memory: &Memory, root_prefix: &str, options: &SyncOptions) -> String {
     let scope_str = memory.scope.as_ref()
         .map(|s| format!("({})", s))
         .unwrap_or_default();
@@ -88,20 +324,83 @@ fn format_memory(memory: &Memory) -> String {
         String::new()
     };
     
+    // Breaking changes are highlighted so agents notice contract changes
+    let (breaking_prefix, breaking_marker) = if memory.breaking {
+        ("**⚠️ BREAKING** ", "!")
+    } else {
+        ("", "")
+    };
+    
+    // Multi-line memories continue as an indented block under the bullet
+    let content = memory.content.lines()
+        .collect::<Vec<_>>()
+        .join("\n  ");
+    
+    let authors_str = if !memory.authors.is_empty() {
+        format!(" by {}", memory.authors.join(" & "))
+    } else {
+        String::new()
+    };
+    
+    let refs_str = if !memory.refs.is_empty() {
+        let links: Vec<String> = memory.refs.iter()
+            .map(|reference| reference_link(reference, root_prefix, options))
+            .collect();
+        format!(" — refs: {}", links.join(", "))
+    } else {
+        String::new()
+    };
+    
     format!(
-        "- {}: {} `{}{}: {}` ({}){}\n",
-        memory.content,
+        "- {}{}: {} `{}{}{}: {}` ({}){}{}{} {}{} -->\n",
+        breaking_prefix,
+        content,
         memory.commit_type,
         memory.commit_type,
         scope_str,
+        breaking_marker,
         memory.summary,
         memory.commit_sha,
-        tags_str
+        authors_str,
+        tags_str,
+        refs_str,
+        ID_PREFIX,
+        memory.id
     )
 }
 
+/// Render a reference as Markdown for a CLAUDE.md file
+fn reference_link(reference: &Reference, root_prefix: &str, options: &SyncOptions) -> String {
+    match reference {
+        Reference::Issue { .. } => match &options.issue_url_template {
+            Some(template) => format!("[{}]({})", reference, reference.issue_url(template).unwrap_or_default()),
+            None => reference.to_string(),
+        },
+        Reference::File { path, line } => {
+            let anchor = line.map(|line| format!("#L{}", line)).unwrap_or_default();
+            format!("[{}]({}{}{})", reference, root_prefix, path, anchor)
+        }
+        Reference::Commit { sha } => format!("`{}`", sha),
+        Reference::Url { url } => format!("<{}>", url),
+        Reference::Text { text } => text.clone(),
+    }
+}
+
+/// Relative path from a CLAUDE.md file's directory back to the project root
+fn root_prefix(path: &Path, project_root: &str) -> String {
+    let depth = path.parent()
+        .and_then(|dir| dir.strip_prefix(project_root).ok())
+        .map(|dir| dir.components().filter(|c| matches!(c, std::path::Component::Normal(_))).count());
+    
+    match depth {
+        Some(depth) => "../".repeat(depth),
+        // Outside the project (e.g. a user-global memory): link absolutely
+        None => format!("{}/", project_root.trim_end_matches('/')),
+    }
+}
+
 /// Read existing CLAUDE.md content
-fn read_claude_md(path: &Path) -> Result<String> {
+pub(crate) fn read_claude_md(path: &Path) -> Result<String> {
     if path.exists() {
         let mut content = String::new();
         fs::File::open(path)
@@ -114,9 +413,27 @@ fn read_claude_md(path: &Path) -> Result<String> {
     }
 }
 
+/// First line of the block Synaptic manages in a CLAUDE.md
+const BLOCK_BEGIN: &str = "<!-- synaptic:begin -->";
+/// Last line of the managed block
+const BLOCK_END: &str = "<!-- synaptic:end -->";
+/// Start of the hidden ID at the end of each synced entry
+const ID_PREFIX: &str = "<!-- synaptic:";
+
 /// Find the memories section in CLAUDE.md content
-fn find_memories_section(content: &str) -> Option<(usize, usize)> {
+///
+/// This is the fenced managed block, or in files written before the fences,
+/// the memories heading up to the next heading.
+pub(crate) fn find_memories_section(content: &str) -> Option<(usize, usize)> {
     let lines: Vec<&str> = content.lines().collect();
+    let byte_offset = |line: usize| lines[..line].iter().map(|l| l.len() + 1).sum::<usize>().min(content.len());
+    
+    if let Some(begin) = lines.iter().position(|line| line.trim() == BLOCK_BEGIN) {
+        if let Some(end) = lines[begin..].iter().position(|line| line.trim() == BLOCK_END) {
+            return Some((byte_offset(begin), byte_offset(begin + end + 1)));
+        }
+    }
+    
     let mut start_idx = None;
     let mut end_idx = None;
     
@@ -130,44 +447,168 @@ fn find_memories_section(content: &str) -> Option<(usize, usize)> {
         }
     }
     
-    if let Some(start) = start_idx {
-        let end = end_idx.unwrap_or(lines.len());
-        
-        // Calculate byte positions
-        let start_byte = lines[..start].iter().map(|l| l.len() + 1).sum::<usize>();
-        let end_byte = lines[..end].iter().map(|l| l.len() + 1).sum::<usize>();
+    let start = start_idx?;
+    let end = end_idx.unwrap_or(lines.len());
+    Some((byte_offset(start), byte_offset(end)))
+}
+
+/// Split a section written before the fences into the entries Synaptic wrote
+/// and the lines written by hand, leaving out its heading and byline
+///
+/// Hand-written lines (prose, subsections, bullets without a SHA) keep their
+/// order, with surrounding blank lines trimmed.
+fn split_legacy_section(section: &str) -> (Vec<Vec<&str>>, String) {
+    let lines: Vec<&str> = section.lines().skip(1)
+        .filter(|line| line.trim() != "*Automatically synced by Synaptic*")
+        .collect();
+    let mut synced = Vec::new();
+    let mut handwritten: Vec<&str> = Vec::new();
+    let mut i = 0;
+    
+    while i < lines.len() {
+        if !lines[i].starts_with("- ") {
+            handwritten.push(lines[i]);
+            i += 1;
+            continue;
+        }
         
-        Some((start_byte, end_byte))
-    } else {
-        None
+        let mut entry_end = i + 1;
+        while entry_end < lines.len() && lines[entry_end].starts_with("  ") && !lines[entry_end].trim().is_empty() {
+            entry_end += 1;
+        }
+        let entry = lines[i..entry_end].to_vec();
+        if entry_sha(&entry).is_some() {
+            synced.push(entry);
+        } else {
+            handwritten.extend(entry);
+        }
+        i = entry_end;
+    }
+    
+    (synced, handwritten.join("\n").trim_matches('\n').to_string())
+}
+
+/// Split a memories section into its entries: a bullet plus its indented continuation lines
+pub(crate) fn memory_entries(section: &str) -> Vec<Vec<&str>> {
+    let mut entries: Vec<Vec<&str>> = Vec::new();
+    let mut in_memory = false;
+    
+    for line in section.lines() {
+        if line.starts_with("- ") {
+            entries.push(vec![line]);
+            in_memory = true;
+        } else if in_memory && line.starts_with("  ") && !line.trim().is_empty() {
+            if let Some(entry) = entries.last_mut() {
+                entry.push(line);
+            }
+        } else {
+            in_memory = false;
+        }
+    }
+    
+    entries
+}
+
+/// Hidden ID of a synced entry, split into SHA and memory index
+pub(crate) fn entry_id<'a>(entry: &[&'a str]) -> Option<(&'a str, &'a str)> {
+    let last = entry.last()?;
+    let start = last.rfind(ID_PREFIX)? + ID_PREFIX.len();
+    let end = start + last[start..].find(" -->")?;
+    last[start..end].rsplit_once(':')
+}
+
+/// SHA a CLAUDE.md entry was synced from: its hidden ID's, or the `(sha)` after the header
+pub(crate) fn entry_sha<'a>(entry: &[&'a str]) -> Option<&'a str> {
+    if let Some((sha, _)) = entry_id(entry) {
+        return Some(sha);
     }
+    entry.iter().find_map(|line| {
+        let start = line.find("` (")? + 3;
+        let len = line[start..].find(')')?;
+        let sha = &line[start..start + len];
+        (sha.len() >= 7 && sha.chars().all(|c| c.is_ascii_hexdigit())).then_some(sha)
+    })
 }
 
-/// Check if a memory is already present in the content
+/// Whether a CLAUDE.md entry is struck through as reverted
+pub(crate) fn entry_is_retracted(entry: &[&str]) -> bool {
+    entry.first().is_some_and(|line| line.starts_with("- ~~"))
+}
+
+/// Check if a memory is already present in content without hidden IDs
 fn memory_already_exists(content: &str, memory: &Memory) -> bool {
-    // Look for the memory content and commit SHA
-    content.contains(&memory.content) && content.contains(&memory.commit_sha)
+    // Look for the memory content (line by line, since it may be wrapped) and
+    // the SHA of the commit or of any copy of it
+    memory.content.lines().all(|line| content.contains(line.trim())) &&
+        std::iter::once(&memory.commit_sha).chain(&memory.duplicates).any(|sha| content.contains(sha.as_str()))
 }
 
-/// Filter out memories that are already present
-fn filter_new_memories(path: &Path, memories: &[Memory]) -> Result<Vec<Memory>> {
+impl Memory {
+    /// Whether a CLAUDE.md entry was synced from this memory
+    ///
+    /// Entries with a hidden ID match on the SHA of the commit or of any
+    /// copy, and the memory index; older entries fall back to their text.
+    fn matches_entry(&self, entry: &[&str]) -> bool {
+        let Some((sha, index)) = entry_id(entry) else {
+            return memory_already_exists(&entry.join("\n"), self);
+        };
+        let (own_sha, own_index) = self.id.rsplit_once(':').unwrap_or((&self.id, ""));
+        
+        index == own_index && (
+            own_sha.starts_with(sha) ||
+            std::iter::once(&self.commit_sha).chain(&self.duplicates).any(|short| sha.starts_with(short.as_str()))
+        )
+    }
+}
+
+/// Update or create CLAUDE.md with memories
+///
+/// Only the managed block is rewritten. Entries already synced from a memory
+/// are matched by their hidden ID and re-rendered; the memories of this sync
+/// come first, newest first, followed by every other entry in its old order.
+fn update_claude_md(path: &Path, memories: &[Memory], dry_run: bool, project_root: &str, options: &SyncOptions) -> Result<()> {
+    // A `Location:` or configured target in a directory that doesn't exist
+    // is a typo or a deleted module; don't create it
+    if path.parent().is_some_and(|parent| !parent.is_dir()) {
+        let verb = if dry_run { "Would skip:" } else { "⚠️  Skipped:" };
+        println!("{} {} (directory doesn't exist)", verb.yellow(), path.display());
+        return Ok(());
+    }
+    
     let existing_content = read_claude_md(path)?;
+    let root_prefix = root_prefix(path, project_root);
+    let section = find_memories_section(&existing_content);
+    // A section written before the fences only has Synaptic's entries fenced;
+    // what was written by hand stays below the block
+    let (mut existing_entries, handwritten) = match section {
+        Some((start, end)) if existing_content[start..].trim_start().starts_with(BLOCK_BEGIN) => {
+            (memory_entries(&existing_content[start..end]), String::new())
+        }
+        Some((start, end)) => split_legacy_section(&existing_content[start..end]),
+        None => (Vec::new(), String::new()),
+    };
     
-    let new_memories: Vec<Memory> = memories
-        .iter()
-        .filter(|memory| !memory_already_exists(&existing_content, memory))
-        .cloned()
-        .collect();
+    // Sort memories by timestamp (newest first)
+    let mut sorted_memories = memories.to_vec();
+    sorted_memories.sort_by_key(|memory| std::cmp::Reverse(memory.timestamp));
     
-    Ok(new_memories)
-}
-
-/// Update or create CLAUDE.md with new memories
-fn update_claude_md(path: &Path, memories: &[Memory], dry_run: bool) -> Result<()> {
-    // Filter out memories that already exist
-    let new_memories = filter_new_memories(path, memories)?;
+    let mut entries = Vec::new();
+    let mut new_memories = Vec::new();
+    let mut updated = 0;
+    for memory in &sorted_memories {
+        let formatted = format_memory(memory, &root_prefix, options);
+        match existing_entries.iter().position(|entry| memory.matches_entry(entry)) {
+            Some(i) => {
+                if existing_entries.remove(i).join("\n") != formatted.trim_end() {
+                    updated += 1;
+                }
+            }
+            None => new_memories.push(memory.clone()),
+        }
+        entries.push(formatted);
+    }
     
-    if new_memories.is_empty() {
+    if new_memories.is_empty() && updated == 0 {
         if dry_run {
             println!("{} {} (no new memories)", 
                 "Would skip:".bright_black(), 
@@ -182,69 +623,55 @@ fn update_claude_md(path: &Path, memories: &[Memory], dry_run: bool) -> Result<(
         return Ok(());
     }
     
-    let existing_content = read_claude_md(path)?;
-    
-    // Format new memories
-    let mut memory_lines = Vec::new();
-    memory_lines.push("\n## SVCMS Memories\n\n".to_string());
-    memory_lines.push("*Automatically synced by Synaptic*\n\n".to_string());
-    
-    // Sort new memories by timestamp (newest first)
-    let mut sorted_new_memories = new_memories.clone();
-    sorted_new_memories.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-    
-    // If there are existing memories, we need to merge them properly
-    if let Some((start, end)) = find_memories_section(&existing_content) {
-        // Extract existing memories and combine with new ones
-        let existing_memories_text = &existing_content[start..end];
-        let existing_memory_lines: Vec<&str> = existing_memories_text
-            .lines()
-            .filter(|line| line.starts_with("- "))
-            .collect();
-        
-        // Add new memories first (they're newer)
-        for memory in &sorted_new_memories {
-            memory_lines.push(format_memory(memory));
-        }
-        
-        // Then add existing memories
-        for line in existing_memory_lines {
-            memory_lines.push(format!("{}\n", line));
-        }
-    } else {
-        // No existing memories section, just add new ones
-        for memory in &sorted_new_memories {
-            memory_lines.push(format_memory(memory));
-        }
+    // Entries of commits outside this sync, and any written by hand, stay as they are
+    for entry in existing_entries {
+        entries.push(format!("{}\n", entry.join("\n")));
     }
     
-    let new_memory_section = memory_lines.join("");
+    let new_memory_section = format!(
+        "{}\n## SVCMS Memories\n\n*Automatically synced by Synaptic*\n\n{}{}\n",
+        BLOCK_BEGIN,
+        entries.join(""),
+        BLOCK_END
+    );
     
     // Determine new content
-    let new_content = if let Some((start, end)) = find_memories_section(&existing_content) {
-        // Replace existing section
+    let new_content = if let Some((start, end)) = section {
+        // Replace existing section, keeping a blank line before what follows
+        let rest = &existing_content[end..];
+        let separator = if rest.is_empty() || rest.starts_with('\n') { "" } else { "\n" };
+        let handwritten = if handwritten.is_empty() { String::new() } else { format!("\n{}\n", handwritten) };
         format!(
-            "{}{}{}",
+            "{}{}{}{}{}",
             &existing_content[..start],
             new_memory_section,
-            &existing_content[end..]
+            handwritten,
+            separator,
+            rest
         )
     } else if existing_content.is_empty() {
         // Create new file with header
         format!(
-            "# CLAUDE.md\n\nThis file provides guidance to Claude Code (claude.ai/code) when working with code in this repository.\n{}",
+            "# CLAUDE.md\n\nThis file provides guidance to Claude Code (claude.ai/code) when working with code in this repository.\n\n{}",
             new_memory_section
         )
     } else {
         // Append to existing file
-        format!("{}\n{}", existing_content.trim_end(), new_memory_section)
+        format!("{}\n\n{}", existing_content.trim_end(), new_memory_section)
+    };
+    
+    let counts = if updated > 0 {
+        format!("{} new, {} updated memories", new_memories.len(), updated)
+    } else {
+        format!("{} new memories", new_memories.len())
     };
     
     if dry_run {
-        println!("\n{} {}", "Would update:".yellow(), path.display());
+        println!("\n{} {} ({})", "Would update:".yellow(), path.display(), counts);
+        print_placements(&new_memories);
         println!("{}", "─".repeat(50).bright_black());
         
-        // Show a preview of what would be added
+        // Show a preview of the managed block
         let preview_lines: Vec<&str> = new_memory_section.lines().take(10).collect();
         for line in preview_lines {
             println!("{}", line.bright_black());
@@ -253,31 +680,183 @@ fn update_claude_md(path: &Path, memories: &[Memory], dry_run: bool) -> Result<(
             println!("{}", "... (more memories)".bright_black());
         }
     } else {
-        // Ensure parent directory exists
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)
-                .context("Failed to create directory")?;
-        }
-        
         // Write the file
         let mut file = fs::File::create(path)
             .context("Failed to create CLAUDE.md")?;
         file.write_all(new_content.as_bytes())
             .context("Failed to write CLAUDE.md")?;
         
-        println!("{} {} ({} new memories)", 
+        println!("{} {} ({})", 
             "✓ Updated:".green(), 
             path.display(), 
-            new_memories.len()
+            counts
         );
+        print_placements(&new_memories);
     }
     
     Ok(())
 }
 
+/// Print which rule placed each memory in its file
+fn print_placements(memories: &[Memory]) {
+    for memory in memories {
+        println!("    {} {}", memory.commit_sha.bright_black(), format!("via {}", memory.placement).bright_black());
+    }
+}
+
+/// Rewrite the memory entries of a CLAUDE.md memories section
+///
+/// Each entry (a bullet plus its indented continuation lines) is passed to
+/// `rewrite`, which returns its replacement lines or `None` to keep it.
+/// Returns the new content and the number of entries rewritten.
+pub(crate) fn rewrite_memory_entries(
+    content: &str,
+    mut rewrite: impl FnMut(&[&str]) -> Option<Vec<String>>
+) -> (String, usize) {
+    let Some((start, end)) = find_memories_section(content) else {
+        return (content.to_string(), 0);
+    };
+    
+    let lines: Vec<&str> = content[start..end].lines().collect();
+    let mut kept = Vec::new();
+    let mut rewritten = 0;
+    let mut i = 0;
+    
+    while i < lines.len() {
+        if !lines[i].starts_with("- ") {
+            kept.push(lines[i].to_string());
+            i += 1;
+            continue;
+        }
+        
+        let mut entry_end = i + 1;
+        while entry_end < lines.len() && lines[entry_end].starts_with("  ") && !lines[entry_end].trim().is_empty() {
+            entry_end += 1;
+        }
+        let entry = &lines[i..entry_end];
+        
+        match rewrite(entry) {
+            Some(replacement) => {
+                rewritten += 1;
+                kept.extend(replacement);
+            }
+            None => kept.extend(entry.iter().map(|line| line.to_string())),
+        }
+        
+        i = entry_end;
+    }
+    
+    let mut section = kept.join("\n");
+    if content[start..end].ends_with('\n') {
+        section.push('\n');
+    }
+    
+    (format!("{}{}{}", &content[..start], section, &content[end..]), rewritten)
+}
+
+/// Check if a CLAUDE.md memory entry was synced from the given commit
+fn entry_from_commit(entry: &[&str], commit_sha: &str) -> bool {
+    match entry_id(entry) {
+        Some((sha, _)) => sha.starts_with(commit_sha),
+        None => entry.iter().any(|line| line.contains(&format!("` ({})", commit_sha))),
+    }
+}
+
+/// Suffix appended to the memories of reverted commits when striking them through
+const REVERTED_MARKER: &str = "~~ *(reverted in ";
+
+/// Strike through or remove the memories of reverted commits in CLAUDE.md content
+///
+/// Returns the new content and the number of memories retracted. Memories
+/// already struck through are left alone.
+fn retract_memories(content: &str, reverted: &[RevertedMemory], retraction: Retraction) -> (String, usize) {
+    rewrite_memory_entries(content, |entry| {
+        if entry_is_retracted(entry) {
+            return None;
+        }
+        let revert = reverted.iter().find(|r| entry_from_commit(entry, &r.commit_sha))?;
+        
+        match retraction {
+            Retraction::Remove => Some(Vec::new()),
+            Retraction::Strike => {
+                let mut struck: Vec<String> = entry.iter().map(|line| line.to_string()).collect();
+                struck[0] = format!("- ~~{}", &entry[0][2..]);
+                // The marker goes before the hidden ID, which must end the entry
+                let last = struck.len() - 1;
+                let id_start = struck[last].rfind(ID_PREFIX)
+                    .map(|i| i.saturating_sub(1))
+                    .unwrap_or(struck[last].len());
+                let (text, id) = struck[last].split_at(id_start);
+                struck[last] = format!("{}{}{})*{}", text, REVERTED_MARKER, revert.reverted_by, id);
+                Some(struck)
+            }
+        }
+    })
+}
+
+/// Undo the strike-through of memories whose revert was itself reverted
+fn restore_memories(content: &str, commit_shas: &[&str]) -> (String, usize) {
+    rewrite_memory_entries(content, |entry| {
+        let struck_content = entry[0].strip_prefix("- ~~")?;
+        if !commit_shas.iter().any(|sha| entry_from_commit(entry, sha)) {
+            return None;
+        }
+        
+        let mut restored: Vec<String> = entry.iter().map(|line| line.to_string()).collect();
+        restored[0] = format!("- {}", struck_content);
+        let last = restored.len() - 1;
+        if let Some(marker) = restored[last].rfind(REVERTED_MARKER) {
+            let marker_end = restored[last][marker..].find(")*").map(|i| marker + i + 2).unwrap_or(restored[last].len());
+            restored[last].replace_range(marker..marker_end, "");
+        }
+        Some(restored)
+    })
+}
+
+/// Restore previously retracted memories that are live again
+fn restore_in_claude_md(path: &Path, memories: &[Memory], dry_run: bool) -> Result<()> {
+    let existing_content = read_claude_md(path)?;
+    let commit_shas: Vec<&str> = memories.iter().map(|m| m.commit_sha.as_str()).collect();
+    let (new_content, restored) = restore_memories(&existing_content, &commit_shas);
+    
+    if restored == 0 {
+        return Ok(());
+    }
+    
+    if dry_run {
+        println!("{} {} ({} memories no longer reverted)", "Would restore:".yellow(), path.display(), restored);
+    } else {
+        fs::write(path, new_content)
+            .context("Failed to write CLAUDE.md")?;
+        println!("{} {} ({} memories no longer reverted)", "✓ Restored:".green(), path.display(), restored);
+    }
+    
+    Ok(())
+}
+
+/// Retract reverted memories from one CLAUDE.md file
+fn retract_from_claude_md(path: &Path, reverted: &[RevertedMemory], dry_run: bool, options: &SyncOptions) -> Result<usize> {
+    let existing_content = read_claude_md(path)?;
+    let (new_content, retracted) = retract_memories(&existing_content, reverted, options.retraction);
+    
+    if retracted == 0 {
+        return Ok(0);
+    }
+    
+    if dry_run {
+        println!("{} {} ({} reverted memories)", "Would retract:".yellow(), path.display(), retracted);
+    } else {
+        fs::write(path, new_content)
+            .context("Failed to write CLAUDE.md")?;
+        println!("{} {} ({} reverted memories)", "↩ Retracted:".green(), path.display(), retracted);
+    }
+    
+    Ok(retracted)
+}
+
 /// Sync memories from commits to CLAUDE.md files and optionally Obsidian
-pub fn sync_memories(commits: Vec<SvcmsCommit>, project_root: &str, dry_run: bool) -> Result<()> {
-    sync_memories_with_options(commits, project_root, dry_run, None)
+pub fn sync_memories(commits: Vec<SvcmsCommit>, project_root: &str, dry_run: bool, options: &SyncOptions) -> Result<()> {
+    sync_memories_with_options(commits, project_root, dry_run, options, None)
 }
 
 /// Sync memories with Obsidian integration
@@ -285,10 +864,11 @@ pub fn sync_memories_with_obsidian(
     commits: Vec<SvcmsCommit>, 
     project_root: &str, 
     dry_run: bool,
+    options: &SyncOptions,
     obsidian_manager: &crate::obsidian::ObsidianManager,
     project_name: &str
 ) -> Result<()> {
-    sync_memories_with_options(commits, project_root, dry_run, Some((obsidian_manager, project_name)))
+    sync_memories_with_options(commits, project_root, dry_run, options, Some((obsidian_manager, project_name)))
 }
 
 /// Internal sync function with optional Obsidian integration
@@ -296,9 +876,10 @@ fn sync_memories_with_options(
     commits: Vec<SvcmsCommit>, 
     project_root: &str, 
     dry_run: bool,
+    options: &SyncOptions,
     obsidian_manager: Option<(&crate::obsidian::ObsidianManager, &str)>
 ) -> Result<()> {
-    let memories_by_file = group_memories_by_file(&commits, project_root);
+    let memories_by_file = group_memories_by_file(&commits, project_root, options);
     
     if memories_by_file.is_empty() && commits.is_empty() {
         println!("{}", "No memories found to sync.".yellow());
@@ -314,11 +895,18 @@ fn sync_memories_with_options(
         );
         
         for (path, memories) in &memories_by_file {
-            update_claude_md(path, memories, dry_run)?;
+            restore_in_claude_md(path, memories, dry_run)?;
+            update_claude_md(path, memories, dry_run, project_root, options)?;
             total_memories += memories.len();
         }
     }
     
+    // Retract memories whose commits were reverted
+    let mut total_retracted = 0;
+    for (path, reverted) in &group_retractions_by_file(&commits, project_root, options) {
+        total_retracted += retract_from_claude_md(path, reverted, dry_run, options)?;
+    }
+    
     // Sync to Obsidian if configured
     let mut obsidian_synced = 0;
     if let Some((obsidian, project_name)) = obsidian_manager {
@@ -328,7 +916,7 @@ fn sync_memories_with_options(
             obsidian_synced = obsidian.sync_commits(&commits, project_name)?;
         } else {
             // Count commits with memories for dry run
-            obsidian_synced = commits.iter().filter(|c| c.memory.is_some()).count();
+            obsidian_synced = commits.iter().filter(|c| !c.memory.is_empty()).count();
             println!("\n{} Would sync {} commits to Obsidian vault", 
                 "🔮".bright_black(),
                 obsidian_synced
@@ -337,6 +925,13 @@ fn sync_memories_with_options(
     }
     
     // Final summary
+    if total_retracted > 0 {
+        println!("\n{} {} {} memories from reverted commits",
+            "↩".bright_yellow(),
+            if dry_run { "Would retract" } else { "Retracted" },
+            total_retracted
+        );
+    }
     if !dry_run {
         if obsidian_synced > 0 {
             println!("\n{} Synced {} memories to CLAUDE.md + {} notes to Obsidian", 
@@ -375,61 +970,396 @@ mod tests {
     use super::*;
     use chrono::Utc;
     
+    /// Project root with the given directories
+    fn project(dirs: &[&str]) -> tempfile::TempDir {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for dir in dirs {
+            fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+        }
+        temp_dir
+    }
+    
+    /// Commit parsed from an SVCMS message
+    fn commit(message: &str) -> SvcmsCommit {
+        crate::parser::parse_commit_message("abc1234", message, Utc::now()).unwrap().unwrap()
+    }
+    
     #[test]
     fn test_determine_memory_location() {
-        let commit = SvcmsCommit {
-            sha: "abc123".to_string(),
-            commit_type: "learned".to_string(),
-            scope: Some("parser".to_string()),
-            summary: "test".to_string(),
-            body: None,
-            memory: Some("test memory".to_string()),
-            location: None,
-            context: None,
-            refs: vec![],
-            tags: vec![],
-            timestamp: Utc::now(),
-        };
+        let commit = commit("learned(parser): test\n\nMemory: test memory");
+        
+        let root = project(&["src/parser"]);
+        let location = determine_memory_location(&commit, root.path().to_str().unwrap(), &ScopeGroups::default());
+        assert_eq!(location, root.path().join("src/parser/CLAUDE.md"));
         
-        let location = determine_memory_location(&commit, "/project");
-        assert_eq!(location, PathBuf::from("/project/src/parser/CLAUDE.md"));
+        // A scope without a directory goes to the nearest existing parent
+        let root = project(&[]);
+        let location = determine_memory_location(&commit, root.path().to_str().unwrap(), &ScopeGroups::default());
+        assert_eq!(location, root.path().join("CLAUDE.md"));
     }
     
     #[test]
     fn test_determine_memory_location_with_explicit() {
         let commit = SvcmsCommit {
-            sha: "abc123".to_string(),
-            commit_type: "learned".to_string(),
-            scope: Some("parser".to_string()),
-            summary: "test".to_string(),
-            body: None,
-            memory: Some("test memory".to_string()),
             location: Some("docs/CLAUDE.md".to_string()),
-            context: None,
-            refs: vec![],
-            tags: vec![],
-            timestamp: Utc::now(),
+            ..commit("learned(parser): test\n\nMemory: test memory")
         };
         
-        let location = determine_memory_location(&commit, "/project");
+        let location = determine_memory_location(&commit, "/project", &ScopeGroups::default());
         assert_eq!(location, PathBuf::from("/project/docs/CLAUDE.md"));
     }
     
+    #[test]
+    fn test_locate_memory_from_changed_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/api/client")).unwrap();
+        fs::write(root.join("src/CLAUDE.md"), "# CLAUDE.md\n").unwrap();
+        let root = root.to_str().unwrap();
+        
+        let change = |path: &str| crate::FileChange {
+            path: path.to_string(),
+            old_path: None,
+            status: crate::ChangeStatus::Modified,
+            insertions: 1,
+            deletions: 0,
+        };
+        let commit = |files: Option<Vec<crate::FileChange>>| SvcmsCommit {
+            changed_files: files,
+            ..commit("learned(http): test\n\nMemory: test memory")
+        };
+        
+        let api = commit(Some(vec![change("src/api/client/retry.rs"), change("src/api/client/mod.rs")]));
+        assert_eq!(
+            locate_memory(&api, root, LocationStrategy::CommonDirectory),
+            Path::new(root).join("src/api/client/CLAUDE.md")
+        );
+        assert_eq!(
+            locate_memory(&api, root, LocationStrategy::NearestClaudeMd),
+            Path::new(root).join("src/CLAUDE.md")
+        );
+        assert_eq!(
+            locate_memory(&api, root, LocationStrategy::Scope),
+            Path::new(root).join("src/CLAUDE.md")
+        );
+        
+        // Directories that no longer exist are skipped
+        let deleted = commit(Some(vec![change("src/api/legacy/old.rs"), change("src/api/legacy/older.rs")]));
+        assert_eq!(
+            locate_memory(&deleted, root, LocationStrategy::CommonDirectory),
+            Path::new(root).join("src/api/CLAUDE.md")
+        );
+        
+        // Files spread across the tree land at the root
+        let spread = commit(Some(vec![change("src/api/mod.rs"), change("README.md")]));
+        assert_eq!(
+            locate_memory(&spread, root, LocationStrategy::CommonDirectory),
+            Path::new(root).join("CLAUDE.md")
+        );
+        
+        // Without changed files, fall back to the scope, but never to a
+        // directory that doesn't exist
+        assert_eq!(
+            locate_memory(&commit(None), root, LocationStrategy::CommonDirectory),
+            Path::new(root).join("src/CLAUDE.md")
+        );
+        fs::create_dir_all(Path::new(root).join("src/http")).unwrap();
+        assert_eq!(
+            locate_memory(&commit(None), root, LocationStrategy::CommonDirectory),
+            Path::new(root).join("src/http/CLAUDE.md")
+        );
+        assert_eq!(
+            locate_memory(&commit(None), root, LocationStrategy::NearestClaudeMd),
+            Path::new(root).join("src/CLAUDE.md")
+        );
+        assert_eq!(
+            locate_memory(&api, root, LocationStrategy::Scope),
+            Path::new(root).join("src/http/CLAUDE.md")
+        );
+    }
+    
+    #[test]
+    fn test_place_memory_fallback_chain() {
+        let mut locations = HashMap::new();
+        locations.insert("auth".to_string(), "src/authentication/CLAUDE.md".to_string());
+        locations.insert("api/*".to_string(), "services/api".to_string());
+        locations.insert("docs(*)".to_string(), "docs/CLAUDE.md".to_string());
+        let options = SyncOptions { locations: LocationMap::new(&locations), ..Default::default() };
+        let root = project(&["src/parser"]);
+        let place = |message: &str| {
+            let commit = commit(message);
+            let (path, placement) = place_memory(&commit, commit.memory.first(), root.path().to_str().unwrap(), &options);
+            (path.strip_prefix(root.path()).unwrap().to_path_buf(), placement)
+        };
+        
+        assert_eq!(
+            place("learned(auth): x\n\nMemory: m\nLocation: src/CLAUDE.md"),
+            (PathBuf::from("src/CLAUDE.md"), Placement::Explicit)
+        );
+        assert_eq!(
+            place("learned(auth): x\n\nMemory: m"),
+            (PathBuf::from("src/authentication/CLAUDE.md"), Placement::Config("auth".to_string()))
+        );
+        assert_eq!(
+            place("fix(api/v2): x\n\nMemory: m"),
+            (PathBuf::from("services/api/CLAUDE.md"), Placement::Config("api/*".to_string()))
+        );
+        assert_eq!(
+            place("docs(parser): x\n\nMemory: m"),
+            (PathBuf::from("docs/CLAUDE.md"), Placement::Config("docs(*)".to_string()))
+        );
+        assert_eq!(
+            place("learned(parser): x\n\nMemory: m"),
+            (PathBuf::from("src/parser/CLAUDE.md"), Placement::Scope)
+        );
+        // Scopes without a directory go to its nearest existing parent
+        assert_eq!(
+            place("learned(payments): x\n\nMemory: m"),
+            (PathBuf::from("src/CLAUDE.md"), Placement::Scope)
+        );
+        // Project-wide scopes go to the root without any scope groups configured
+        assert_eq!(
+            place("learned(ci): x\n\nMemory: m"),
+            (PathBuf::from("CLAUDE.md"), Placement::ScopeGroup("project_wide"))
+        );
+        assert_eq!(
+            place("learned: x\n\nMemory: m"),
+            (PathBuf::from("CLAUDE.md"), Placement::Root)
+        );
+    }
+    
     #[test]
     fn test_format_memory() {
         let memory = Memory {
+            id: "abc123:0".to_string(),
             content: "Use lazy_static for regex patterns".to_string(),
             commit_sha: "abc123".to_string(),
             commit_type: "learned".to_string(),
             scope: Some("parser".to_string()),
             summary: "implement regex parsing".to_string(),
-            timestamp: Utc::now(),
+            breaking: false,
+            timestamp: Utc::now().into(),
             tags: vec!["rust".to_string(), "regex".to_string()],
+            refs: vec![],
+            authors: vec!["Ada Lovelace".to_string(), "Grace Hopper".to_string()],
+            duplicates: vec![],
+            placement: Placement::Root,
         };
         
-        let formatted = format_memory(&memory);
+        let formatted = format_memory(&memory, "", &SyncOptions::default());
         assert!(formatted.contains("Use lazy_static for regex patterns"));
         assert!(formatted.contains("learned(parser)"));
-        assert!(formatted.contains("[rust, regex]"));
+        assert!(formatted.contains("(abc123) by Ada Lovelace & Grace Hopper [rust, regex]"));
+        assert!(!formatted.contains("BREAKING"));
+    }
+    
+    #[test]
+    fn test_format_breaking_memory() {
+        let memory = Memory {
+            id: "abc123:0".to_string(),
+            content: "Config key `depth` renamed to `default_depth`".to_string(),
+            commit_sha: "abc123".to_string(),
+            commit_type: "refactor".to_string(),
+            scope: Some("config".to_string()),
+            summary: "rename depth".to_string(),
+            breaking: true,
+            timestamp: Utc::now().into(),
+            tags: vec![],
+            refs: vec![],
+            authors: vec![],
+            duplicates: vec![],
+            placement: Placement::Root,
+        };
+        
+        let formatted = format_memory(&memory, "", &SyncOptions::default());
+        assert!(formatted.starts_with("- **⚠️ BREAKING** Config key"));
+        assert!(formatted.contains("`refactor(config)!: rename depth`"));
+    }
+    
+    #[test]
+    fn test_format_memory_reference_links() {
+        let memory = Memory {
+            id: "abc123:0".to_string(),
+            content: "Retry after the reset".to_string(),
+            commit_sha: "abc123".to_string(),
+            commit_type: "learned".to_string(),
+            scope: Some("api".to_string()),
+            summary: "rate limits".to_string(),
+            breaking: false,
+            timestamp: Utc::now().into(),
+            tags: vec![],
+            refs: vec![
+                Reference::Issue { number: 87 },
+                Reference::File { path: "src/api/client.ts".to_string(), line: Some(42) },
+                Reference::Commit { sha: "a1b2c3d".to_string() },
+            ],
+            authors: vec![],
+            duplicates: vec![],
+            placement: Placement::Root,
+        };
+        let options = SyncOptions {
+            issue_url_template: Some("https://github.com/acme/app/issues/{number}".to_string()),
+            ..Default::default()
+        };
+        
+        let prefix = root_prefix(Path::new("/project/src/api/CLAUDE.md"), "/project");
+        assert_eq!(prefix, "../../");
+        
+        let formatted = format_memory(&memory, &prefix, &options);
+        assert!(formatted.ends_with(
+            " — refs: [#87](https://github.com/acme/app/issues/87), [src/api/client.ts:42](../../src/api/client.ts#L42), `a1b2c3d` <!-- synaptic:abc123:0 -->\n"
+        ));
+        
+        let formatted = format_memory(&memory, "", &SyncOptions::default());
+        assert!(formatted.contains("refs: #87, [src/api/client.ts:42](src/api/client.ts#L42)"));
+    }
+    
+    #[test]
+    fn test_retract_memories() {
+        let content = "# CLAUDE.md\n\n## SVCMS Memories\n\n\
+- Retry 429s immediately: learned `learned(api): retry on 429` (abc1234)\n\
+- Multi-line\n  insight: learned `learned(api): split` (def5678)\n\
+- Tokens are cacheable: learned `learned(api): cache tokens` (fff0000)\n\n## Notes\n";
+        let reverted = vec![
+            RevertedMemory { commit_sha: "abc1234".to_string(), reverted_by: "9999999".to_string() },
+            RevertedMemory { commit_sha: "def5678".to_string(), reverted_by: "9999999".to_string() },
+        ];
+        
+        let (struck, count) = retract_memories(content, &reverted, Retraction::Strike);
+        assert_eq!(count, 2);
+        assert!(struck.contains("- ~~Retry 429s immediately: learned `learned(api): retry on 429` (abc1234)~~ *(reverted in 9999999)*\n"));
+        assert!(struck.contains("- ~~Multi-line\n  insight: learned `learned(api): split` (def5678)~~ *(reverted in 9999999)*\n"));
+        assert!(struck.contains("- Tokens are cacheable"));
+        assert!(struck.ends_with("\n## Notes\n"));
+        
+        // Already struck memories stay as they are
+        assert_eq!(retract_memories(&struck, &reverted, Retraction::Strike), (struck.clone(), 0));
+        
+        let (removed, count) = retract_memories(content, &reverted, Retraction::Remove);
+        assert_eq!(count, 2);
+        assert!(!removed.contains("abc1234"));
+        assert!(!removed.contains("insight"));
+        assert!(removed.contains("- Tokens are cacheable"));
+        
+        // Reverting the revert restores the original lines
+        assert_eq!(restore_memories(&struck, &["abc1234", "def5678"]), (content.to_string(), 2));
+    }
+    
+    #[test]
+    fn test_update_claude_md_managed_block() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path().to_str().unwrap();
+        let path = temp_dir.path().join("CLAUDE.md");
+        let memory = |id: &str, content: &str, minutes_ago: i64| Memory {
+            id: id.to_string(),
+            content: content.to_string(),
+            commit_sha: id[..7].to_string(),
+            commit_type: "learned".to_string(),
+            scope: None,
+            summary: "x".to_string(),
+            breaking: false,
+            timestamp: (Utc::now() - chrono::Duration::minutes(minutes_ago)).into(),
+            tags: vec![],
+            refs: vec![],
+            authors: vec![],
+            duplicates: vec![],
+            placement: Placement::Root,
+        };
+        let older = memory("aaaaaaa111:0", "Old insight", 10);
+        let newer = memory("bbbbbbb222:0", "New insight", 5);
+        
+        // A section written before the fences, with an entry, prose and a
+        // subsection added by hand
+        fs::write(&path, "# CLAUDE.md\n\nIntro.\n\n## SVCMS Memories\n\n*Automatically synced by Synaptic*\n\n\
+- Old insight: learned `learned: x` (aaaaaaa)\n- My own note\n\nRead these first.\n\n### Pitfalls\n\nMind the cache.\n\n## Notes\n\nKeep me.\n").unwrap();
+        update_claude_md(&path, std::slice::from_ref(&older), false, root, &SyncOptions::default()).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# CLAUDE.md\n\nIntro.\n\n<!-- synaptic:begin -->\n## SVCMS Memories\n"));
+        assert!(content.contains("- Old insight: learned `learned: x` (aaaaaaa) <!-- synaptic:aaaaaaa111:0 -->\n<!-- synaptic:end -->\n\n\
+- My own note\n\nRead these first.\n\n### Pitfalls\n\nMind the cache.\n\n## Notes\n\nKeep me.\n"));
+        
+        // Reworded entries are still found by their ID, and newer memories go first
+        fs::write(&path, content.replace("- Old insight:", "- Old, reworded:")).unwrap();
+        update_claude_md(&path, &[older.clone(), newer], false, root, &SyncOptions::default()).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content.matches("aaaaaaa111:0").count(), 1);
+        assert!(!content.contains("reworded"));
+        let new_at = content.find("New insight").unwrap();
+        assert!(new_at < content.find("Old insight").unwrap());
+        assert!(content.find("My own note").unwrap() > new_at);
+        
+        // Nothing changes on a repeated sync
+        update_claude_md(&path, std::slice::from_ref(&older), false, root, &SyncOptions::default()).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        
+        // Targets in directories that don't exist are skipped, not created
+        let missing = temp_dir.path().join("src/payments/CLAUDE.md");
+        update_claude_md(&missing, &[older], false, root, &SyncOptions::default()).unwrap();
+        assert!(!temp_dir.path().join("src").exists());
+        
+        // Retraction keeps the ID at the end of the entry
+        let reverted = vec![RevertedMemory { commit_sha: "aaaaaaa".to_string(), reverted_by: "9999999".to_string() }];
+        let (struck, count) = retract_memories(&content, &reverted, Retraction::Strike);
+        assert_eq!(count, 1);
+        assert!(struck.contains("(aaaaaaa)~~ *(reverted in 9999999)* <!-- synaptic:aaaaaaa111:0 -->\n"));
+        assert_eq!(restore_memories(&struck, &["aaaaaaa"]), (content, 1));
+    }
+    
+    #[test]
+    fn test_reverted_commits_are_not_synced() {
+        let commit = SvcmsCommit {
+            reverted_by: Some("9999999".to_string()),
+            duplicates: vec!["def5678".to_string()],
+            ..commit("learned(api): retry on 429\n\nMemory: Retry 429s immediately")
+        };
+        
+        let root = project(&["src/api"]);
+        let root_path = root.path().to_str().unwrap();
+        assert!(group_memories_by_file(std::slice::from_ref(&commit), root_path, &SyncOptions::default()).is_empty());
+        let retractions = group_retractions_by_file(&[commit], root_path, &SyncOptions::default());
+        let retractions = &retractions[&root.path().join("src/api/CLAUDE.md")];
+        assert_eq!(retractions[0].reverted_by, "9999999");
+        // Copies synced under another SHA are retracted too
+        let shas: Vec<&str> = retractions.iter().map(|r| r.commit_sha.as_str()).collect();
+        assert_eq!(shas, vec!["abc1234", "def5678"]);
+    }
+    
+    #[test]
+    fn test_group_memories_by_file_per_entry() {
+        let commit = SvcmsCommit {
+            memory: vec![
+                MemoryEntry { content: "first".to_string(), location: Some("docs/CLAUDE.md".to_string()) },
+                MemoryEntry { content: "second\nwith snippet".to_string(), location: None },
+            ],
+            ..commit("learned(parser): test")
+        };
+        
+        let root = project(&["src/parser"]);
+        let grouped = group_memories_by_file(std::slice::from_ref(&commit), root.path().to_str().unwrap(), &SyncOptions::default());
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(grouped[&root.path().join("docs/CLAUDE.md")][0].content, "first");
+        assert_eq!(grouped[&root.path().join("docs/CLAUDE.md")][0].id, "abc1234:0");
+        
+        let second = &grouped[&root.path().join("src/parser/CLAUDE.md")][0];
+        let formatted = format_memory(second, "../../", &SyncOptions::default());
+        assert!(formatted.starts_with("- second\n  with snippet: learned"));
+        assert!(memory_already_exists(&formatted, second));
+        
+        // A squash piece numbers its memories from its place in the message,
+        // even when the pieces before it aren't synced
+        let piece = SvcmsCommit { memory_offset: 3, ..commit };
+        let grouped = group_memories_by_file(&[piece], root.path().to_str().unwrap(), &SyncOptions::default());
+        assert_eq!(grouped[&root.path().join("docs/CLAUDE.md")][0].id, "abc1234:3");
+        assert_eq!(grouped[&root.path().join("src/parser/CLAUDE.md")][0].id, "abc1234:4");
+    }
+    
+    #[test]
+    fn test_promote_breaking_changes() {
+        let breaking = commit("feat(api)!: drop v1 endpoints\n\nBREAKING CHANGE: v1 endpoints removed");
+        let mut commits = vec![breaking.clone(), SvcmsCommit { breaking: false, ..breaking }];
+        
+        assert_eq!(promote_breaking_changes(&mut commits), 1);
+        assert_eq!(commits[0].memory[0].content, "v1 endpoints removed");
+        assert!(commits[1].memory.is_empty());
     }
 }
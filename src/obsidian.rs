@@ -6,13 +6,14 @@ use std::io::Write;
 use anyhow::{Result, Context, anyhow};
 use handlebars::Handlebars;
 use serde_json::json;
-use crate::SvcmsCommit;
+use crate::{SvcmsCommit, Reference};
 
 /// Manages Obsidian vault operations for Synaptic
 pub struct ObsidianManager {
     vault_path: PathBuf,
     synaptic_folder: String,
     template_engine: Handlebars<'static>,
+    issue_url_template: Option<String>,
 }
 
 impl ObsidianManager {
@@ -35,9 +36,16 @@ impl ObsidianManager {
             vault_path,
             synaptic_folder,
             template_engine,
+            issue_url_template: None,
         })
     }
 
+    /// Link `#<number>` references using an issue tracker URL template
+    pub fn with_issue_url_template(mut self, template: Option<String>) -> Self {
+        self.issue_url_template = template;
+        self
+    }
+
     /// Get the path to the synaptic folder within the vault
     pub fn synaptic_path(&self) -> PathBuf {
         self.vault_path.join(&self.synaptic_folder)
@@ -83,8 +91,14 @@ impl ObsidianManager {
         let filename = generate_note_filename(commit);
         let note_path = commits_dir.join(filename);
 
-        // Skip if note already exists
+        // Existing notes are kept, only marked once their commit is reverted
         if note_path.exists() {
+            if let Some(reverted_by) = &commit.reverted_by {
+                let content = fs::read_to_string(&note_path)?;
+                if let Some(marked) = mark_note_reverted(&content, reverted_by) {
+                    fs::write(&note_path, marked)?;
+                }
+            }
             return Ok(());
         }
 
@@ -92,18 +106,27 @@ impl ObsidianManager {
         let template_data = json!({
             "commit_sha": commit.sha,
             "commit_type": commit.commit_type,
+            "commit_category": commit.category.as_deref().unwrap_or(""),
             "commit_scope": commit.scope.as_deref().unwrap_or(""),
             "commit_summary": commit.summary,
+            "breaking": commit.breaking,
+            "breaking_change": commit.breaking_change.as_deref().unwrap_or(""),
             "commit_body": commit.body.as_deref().unwrap_or(""),
             "commit_date": commit.timestamp.format("%Y-%m-%d").to_string(),
-            "memory_field": commit.memory.as_deref().unwrap_or(""),
+            "memories": memory_template_data(commit),
             "context_field": commit.context.as_deref().unwrap_or(""),
             "project_name": project_name,
-            "refs": commit.refs,
+            "refs": commit.refs.iter().map(|r| r.to_string()).collect::<Vec<_>>(),
+            "references": commit.refs.iter().map(|r| self.reference_link(r)).collect::<Vec<_>>(),
             "tags": commit.tags,
+            "footers": footer_template_data(commit),
             "is_empty_commit": commit.body.is_none(),
+            "reverted_by": commit.reverted_by,
+            "duplicates": commit.duplicates,
+            "files": commit.changed_files,
             "extracted_concepts": extract_concepts(commit),
-            "author": "Corrado & Claude" // TODO: Extract from git commit
+            "authors": commit.author_names(),
+            "author": commit.author_names().join(" & "),
         });
 
         // Render template
@@ -117,6 +140,24 @@ impl ObsidianManager {
         Ok(())
     }
 
+    /// Render a reference as Markdown for a commit note
+    ///
+    /// Commits become `[[sha]]` wikilinks, resolved through the short SHA
+    /// every commit note carries as an alias.
+    fn reference_link(&self, reference: &Reference) -> String {
+        match reference {
+            Reference::Issue { .. } => match &self.issue_url_template {
+                Some(template) => format!("[{}]({})", reference, reference.issue_url(template).unwrap_or_default()),
+                // A bare `#87` would be read as a tag
+                None => format!("`{}`", reference),
+            },
+            Reference::File { .. } => format!("`{}`", reference),
+            Reference::Commit { sha } => format!("[[{}]]", &sha[..sha.len().min(7)]),
+            Reference::Url { url } => format!("<{}>", url),
+            Reference::Text { text } => text.clone(),
+        }
+    }
+
     /// Sync a batch of commits to Obsidian
     pub fn sync_commits(&self, commits: &[SvcmsCommit], project_name: &str) -> Result<usize> {
         let mut synced_count = 0;
@@ -126,7 +167,7 @@ impl ObsidianManager {
 
         for commit in commits {
             // Only sync commits with memories
-            if commit.memory.is_some() {
+            if !commit.memory.is_empty() {
                 self.create_commit_note(commit, project_name)?;
                 synced_count += 1;
             }
@@ -159,6 +200,63 @@ fn generate_note_filename(commit: &SvcmsCommit) -> String {
     format!("{}-{}-{}-{}.md", date, commit.commit_type, scope, summary)
 }
 
+/// Mark an existing commit note as reverted, unless it already is
+fn mark_note_reverted(content: &str, reverted_by: &str) -> Option<String> {
+    if content.lines().any(|line| line.starts_with("reverted_by:")) {
+        return None;
+    }
+    
+    let mut marked = Vec::new();
+    let mut frontmatter_delimiters = 0;
+    
+    for line in content.lines() {
+        if line == "---" {
+            frontmatter_delimiters += 1;
+            // Closing frontmatter delimiter
+            if frontmatter_delimiters == 2 {
+                marked.push(format!("reverted_by: {}", reverted_by));
+            }
+        }
+        marked.push(line.to_string());
+        
+        if line.starts_with("# ") && frontmatter_delimiters >= 2 {
+            marked.push(String::new());
+            marked.push(reverted_callout(reverted_by).trim_end().to_string());
+        }
+    }
+    
+    Some(marked.join("\n") + "\n")
+}
+
+/// Callout placed under the title of a reverted commit's note
+fn reverted_callout(reverted_by: &str) -> String {
+    format!(
+        "> [!failure] Reverted\n> Rolled back in [[{}]]; this insight no longer reflects the code.\n",
+        reverted_by
+    )
+}
+
+/// Prepare each memory for the template: an indented block for the note
+/// body and a single-line form for frontmatter
+fn memory_template_data(commit: &SvcmsCommit) -> Vec<serde_json::Value> {
+    commit.memory.iter()
+        .map(|entry| json!({
+            "text": entry.content.lines().collect::<Vec<_>>().join("\n  "),
+            "inline": entry.content.lines().map(str::trim).collect::<Vec<_>>().join(" "),
+            "location": entry.location,
+        }))
+        .collect()
+}
+
+/// Flatten unrecognized footers into token/value pairs for the template
+fn footer_template_data(commit: &SvcmsCommit) -> Vec<serde_json::Value> {
+    commit.footers.iter()
+        .flat_map(|(token, values)| {
+            values.iter().map(move |value| json!({ "token": token, "value": value }))
+        })
+        .collect()
+}
+
 /// Extract concepts from commit for wikilink generation
 fn extract_concepts(commit: &SvcmsCommit) -> Vec<String> {
     let mut concepts = Vec::new();
@@ -199,22 +297,47 @@ fn extract_concepts(commit: &SvcmsCommit) -> Vec<String> {
 const DEFAULT_COMMIT_TEMPLATE: &str = r#"---
 id: {{commit_sha}}
 type: {{commit_type}}
+category: {{commit_category}}
+breaking: {{breaking}}
 scope: {{commit_scope}}
 date: {{commit_date}}
 tags: {{#each tags}}{{this}}{{#unless @last}}, {{/unless}}{{/each}}
-memory: "{{memory_field}}"
+memory: [{{#each memories}}"{{inline}}"{{#unless @last}}, {{/unless}}{{/each}}]
 project: {{project_name}}
+authors: [{{#each authors}}"{{this}}"{{#unless @last}}, {{/unless}}{{/each}}]
 refs: {{#each refs}}["{{this}}"]{{#unless @last}}, {{/unless}}{{/each}}
-aliases: ["{{commit_summary}}"]
+aliases: ["{{commit_summary}}", "{{commit_sha}}"{{#each duplicates}}, "{{this}}"{{/each}}]
+{{#if reverted_by}}
+reverted_by: {{reverted_by}}
+{{/if}}
 ---
 
 # {{commit_type}}({{commit_scope}}): {{commit_summary}}
 
+{{#if reverted_by}}
+> [!failure] Reverted
+> Rolled back in [[{{reverted_by}}]]; this insight no longer reflects the code.
+
+{{/if}}
+{{#if breaking}}
+> [!warning] Breaking change
+> {{#if breaking_change}}{{breaking_change}}{{else}}{{commit_summary}}{{/if}}
+
+{{/if}}
 ## What Changed
 {{commit_body}}
+{{#if files}}
+
+## Files touched
+{{#each files}}
+- `{{#if old_path}}{{old_path}} → {{/if}}{{path}}` {{status}} (+{{insertions}} -{{deletions}})
+{{/each}}
+{{/if}}
 
-## Key Insight
-{{memory_field}}
+## Key Insights
+{{#each memories}}
+- {{text}}{{#if location}} → `{{location}}`{{/if}}
+{{/each}}
 
 {{#if is_empty_commit}}
 > 📝 This was a pure knowledge commit (no code changes)
@@ -229,15 +352,22 @@ aliases: ["{{commit_summary}}"]
 {{/each}}
 
 ## References
-{{#each refs}}
-- {{this}}
+{{#each references}}
+- {{{this}}}
+{{/each}}
+{{#if footers}}
+
+## Footers
+{{#each footers}}
+- **{{token}}**: {{value}}
 {{/each}}
+{{/if}}
 
 ## Project Context
 ![[projects/{{project_name}}/_index#Current Focus]]
 
 ---
-*Commit: {{commit_sha}} | Author: {{author}} | Date: {{commit_date}}*
+*Commit: {{commit_sha}}{{#if author}} | Author: {{{author}}}{{/if}} | Date: {{commit_date}}*
 "#;
 
 /// Template for the main Synaptic index
@@ -278,22 +408,42 @@ SORT file.name ASC
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{ChangeStatus, FileChange, MemoryEntry, Person};
     use chrono::Utc;
     use tempfile::TempDir;
 
     fn create_test_commit() -> SvcmsCommit {
         SvcmsCommit {
             sha: "abc1234".to_string(),
+            full_sha: None,
+            category: Some("knowledge".to_string()),
             commit_type: "learned".to_string(),
             scope: Some("auth".to_string()),
             summary: "JWT tokens have 24h expiry".to_string(),
+            breaking: false,
+            breaking_change: None,
             body: Some("Discovered that our JWT implementation uses 24-hour expiry.".to_string()),
-            memory: Some("JWT tokens expire after 24 hours".to_string()),
+            memory: vec![
+                MemoryEntry { content: "JWT tokens expire after 24 hours".to_string(), location: None },
+                MemoryEntry { content: "Refresh tokens rotate\non every use".to_string(), location: Some("src/auth/CLAUDE.md".to_string()) },
+            ],
+            memory_offset: 0,
             location: None,
             context: Some("Authentication debugging session".to_string()),
-            refs: vec!["#123".to_string()],
+            refs: vec![
+                Reference::Issue { number: 123 },
+                Reference::Commit { sha: "def5678".to_string() },
+            ],
             tags: vec!["auth".to_string(), "jwt".to_string()],
-            timestamp: Utc::now(),
+            footers: Default::default(),
+            timestamp: Utc::now().into(),
+            reverted_by: None,
+            cherry_picked_from: None,
+            duplicates: Vec::new(),
+            author: Some(Person::new("Ada Lovelace", Some("ada@example.com".to_string()))),
+            committer: None,
+            co_authors: vec![Person::parse("Grace Hopper <grace@example.com>")],
+            changed_files: None,
         }
     }
 
@@ -337,4 +487,99 @@ mod tests {
         assert!(temp_dir.path().join("synaptic/concepts").exists());
         assert!(temp_dir.path().join("synaptic/_synaptic_index.md").exists());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_commit_note_renders_each_memory() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ObsidianManager::new(
+            temp_dir.path().to_path_buf(),
+            "synaptic".to_string()
+        ).unwrap();
+        
+        let commit = create_test_commit();
+        manager.create_commit_note(&commit, "test-project").unwrap();
+        
+        let note_path = manager.commits_path("test-project").join(generate_note_filename(&commit));
+        let content = fs::read_to_string(note_path).unwrap();
+        assert!(content.contains(r#"memory: ["JWT tokens expire after 24 hours", "Refresh tokens rotate on every use"]"#));
+        assert!(content.contains("- JWT tokens expire after 24 hours\n"));
+        assert!(content.contains("- Refresh tokens rotate\n  on every use → `src/auth/CLAUDE.md`"));
+        assert!(content.contains(r#"authors: ["Ada Lovelace", "Grace Hopper"]"#));
+        assert!(content.contains("| Author: Ada Lovelace & Grace Hopper |"));
+        assert!(!content.contains("## Files touched"));
+    }
+
+    #[test]
+    fn test_commit_note_lists_files_touched() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ObsidianManager::new(
+            temp_dir.path().to_path_buf(),
+            "synaptic".to_string()
+        ).unwrap();
+        
+        let mut commit = create_test_commit();
+        commit.changed_files = Some(vec![
+            FileChange { path: "src/auth/jwt.rs".to_string(), old_path: None, status: ChangeStatus::Modified, insertions: 12, deletions: 3 },
+            FileChange {
+                path: "src/auth/session.rs".to_string(),
+                old_path: Some("src/session.rs".to_string()),
+                status: ChangeStatus::Renamed,
+                insertions: 0,
+                deletions: 0,
+            },
+        ]);
+        manager.create_commit_note(&commit, "test-project").unwrap();
+        
+        let note_path = manager.commits_path("test-project").join(generate_note_filename(&commit));
+        let content = fs::read_to_string(note_path).unwrap();
+        assert!(content.contains("## Files touched\n- `src/auth/jwt.rs` modified (+12 -3)\n- `src/session.rs → src/auth/session.rs` renamed (+0 -0)\n"));
+    }
+
+    #[test]
+    fn test_commit_note_marked_reverted() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ObsidianManager::new(
+            temp_dir.path().to_path_buf(),
+            "synaptic".to_string()
+        ).unwrap();
+        
+        let mut commit = create_test_commit();
+        manager.create_commit_note(&commit, "test-project").unwrap();
+        let note_path = manager.commits_path("test-project").join(generate_note_filename(&commit));
+        
+        // A later sync sees the revert and marks the existing note
+        commit.reverted_by = Some("9999999".to_string());
+        manager.create_commit_note(&commit, "test-project").unwrap();
+        manager.create_commit_note(&commit, "test-project").unwrap();
+        
+        let marked = fs::read_to_string(&note_path).unwrap();
+        assert_eq!(marked.matches("reverted_by: 9999999\n---\n").count(), 1);
+        assert_eq!(marked.matches(&reverted_callout("9999999")).count(), 1);
+        
+        // A note created after the revert has the same marks
+        fs::remove_file(&note_path).unwrap();
+        manager.create_commit_note(&commit, "test-project").unwrap();
+        let created = fs::read_to_string(&note_path).unwrap();
+        assert!(created.contains("reverted_by: 9999999\n---\n"));
+        assert!(created.contains(&reverted_callout("9999999")));
+    }
+
+    #[test]
+    fn test_commit_note_links_references() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ObsidianManager::new(
+            temp_dir.path().to_path_buf(),
+            "synaptic".to_string()
+        ).unwrap()
+            .with_issue_url_template(Some("https://github.com/acme/app/issues/{number}".to_string()));
+        
+        let commit = create_test_commit();
+        manager.create_commit_note(&commit, "test-project").unwrap();
+        
+        let note_path = manager.commits_path("test-project").join(generate_note_filename(&commit));
+        let content = fs::read_to_string(note_path).unwrap();
+        assert!(content.contains("- [#123](https://github.com/acme/app/issues/123)\n"));
+        assert!(content.contains("- [[def5678]]\n"));
+        assert!(content.contains(r#"aliases: ["JWT tokens have 24h expiry", "abc1234"]"#));
+    }
+}
@@ -2,34 +2,43 @@
 
 use regex::Regex;
 use anyhow::Result;
-use crate::SvcmsCommit;
-use crate::commit_types::CommitTypeValidator;
+use indexmap::IndexMap;
+use crate::{SvcmsCommit, MemoryEntry, Person, Reference};
+use crate::commit_types::{CommitTypeValidator, TypeRejection};
+use crate::diagnostics::{Diagnostic, DiagnosticKind, Span, location_escapes_repository};
 
 lazy_static::lazy_static! {
-    // Header pattern: <type>(<scope>): <summary>
+    // Header pattern: [<category>.]<type>(<scope>)[!]: <summary>
     static ref HEADER_PATTERN: Regex = Regex::new(
-        r"^(\w+)(?:\(([^)]+)\))?:\s*(.+)"
+        r"^(\w+(?:\.\w+)?)(?:\(([^)]+)\))?(!)?:\s*(.+)"
     ).unwrap();
     
-    // Footer patterns for extracting metadata
-    static ref CONTEXT_PATTERN: Regex = Regex::new(
-        r"(?m)^Context:\s*(.+)$"
+    // Trailer line: <Token>: <value> or <Token> #<value>
+    // ("BREAKING CHANGE" is the one token Conventional Commits allows a space in)
+    static ref TRAILER_PATTERN: Regex = Regex::new(
+        r"^(BREAKING CHANGE|[A-Za-z0-9][A-Za-z0-9-]*)(?::\s*(.*)|\s+(#.*))$"
     ).unwrap();
     
-    static ref REFS_PATTERN: Regex = Regex::new(
-        r"(?m)^Refs?:\s*(.+)$"
-    ).unwrap();
+    // Reference patterns
+    static ref ISSUE_REF_PATTERN: Regex = Regex::new(r"^#(\d+)$").unwrap();
+    static ref COMMIT_REF_PATTERN: Regex = Regex::new(r"^[0-9a-f]{7,40}$").unwrap();
+    static ref FILE_LINE_PATTERN: Regex = Regex::new(r"^(.+?)(?::(\d+)|#L(\d+))$").unwrap();
+    static ref FILE_EXTENSION_PATTERN: Regex = Regex::new(r"\.[A-Za-z][A-Za-z0-9]*$").unwrap();
     
-    static ref MEMORY_PATTERN: Regex = Regex::new(
-        r"(?m)^Memory:\s*(.+)$"
+    // Body line `git revert` writes: "This reverts commit <sha>."
+    static ref REVERT_PATTERN: Regex = Regex::new(
+        r"(?m)^This reverts commit ([0-9a-f]{7,40})\b"
     ).unwrap();
     
-    static ref LOCATION_PATTERN: Regex = Regex::new(
-        r"(?m)^Location:\s*(.+)$"
-    ).unwrap();
+    // Squash-merge bullet starting an original commit: "* feat(x): ..." or "- feat(x): ..."
+    static ref SQUASH_BULLET_PATTERN: Regex = Regex::new(r"^[*-] (.+)$").unwrap();
+    
+    // Line GitHub puts between squashed commits and the shared trailers
+    static ref SQUASH_SEPARATOR_PATTERN: Regex = Regex::new(r"^-{3,}$").unwrap();
     
-    static ref TAGS_PATTERN: Regex = Regex::new(
-        r"(?m)^Tags?:\s*(.+)$"
+    // Line `git cherry-pick -x` appends: "(cherry picked from commit <sha>)"
+    static ref CHERRY_PICK_PATTERN: Regex = Regex::new(
+        r"^\(cherry picked from commit ([0-9a-f]{7,40})\)$"
     ).unwrap();
 }
 
@@ -37,7 +46,7 @@ lazy_static::lazy_static! {
 pub fn parse_commit_message(
     sha: &str, 
     message: &str, 
-    timestamp: chrono::DateTime<chrono::Utc>
+    timestamp: impl Into<chrono::DateTime<chrono::FixedOffset>>
 ) -> Result<Option<SvcmsCommit>> {
     parse_commit_message_with_validator(sha, message, timestamp, None)
 }
@@ -46,156 +55,581 @@ pub fn parse_commit_message(
 pub fn parse_commit_message_with_validator(
     sha: &str, 
     message: &str, 
-    timestamp: chrono::DateTime<chrono::Utc>,
+    timestamp: impl Into<chrono::DateTime<chrono::FixedOffset>>,
     validator: Option<&CommitTypeValidator>
 ) -> Result<Option<SvcmsCommit>> {
+    Ok(parse_commit_message_with_diagnostics(sha, message, timestamp, validator).commit)
+}
+
+/// Result of parsing a commit message with diagnostics
+#[derive(Debug, Clone)]
+pub struct ParseOutcome {
+    /// The parsed commit, `None` when any error diagnostic was reported
+    pub commit: Option<SvcmsCommit>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ParseOutcome {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|d| d.is_error())
+    }
+}
+
+/// Parse a commit message, reporting why it was rejected or what looks wrong
+pub fn parse_commit_message_with_diagnostics(
+    sha: &str, 
+    message: &str, 
+    timestamp: impl Into<chrono::DateTime<chrono::FixedOffset>>,
+    validator: Option<&CommitTypeValidator>
+) -> ParseOutcome {
+    let mut diagnostics = Vec::new();
+    
     let lines: Vec<&str> = message.lines().collect();
-    if lines.is_empty() {
-        return Ok(None);
+    if lines.iter().all(|line| line.trim().is_empty()) {
+        diagnostics.push(Diagnostic::new(DiagnosticKind::EmptyMessage, Span::new(1, 1, 0)));
+        return ParseOutcome { commit: None, diagnostics };
     }
     
     // Parse header
     let header = lines[0];
     let captures = match HEADER_PATTERN.captures(header) {
         Some(c) => c,
-        None => return Ok(None), // Not an SVCMS commit
+        None => {
+            // Not an SVCMS commit
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::MalformedHeader,
+                Span::new(1, 1, header.chars().count()),
+            ));
+            return ParseOutcome { commit: None, diagnostics };
+        }
     };
     
-    let commit_type = captures.get(1)
-        .map(|m| m.as_str().to_string())
+    let raw_type = captures.get(1)
+        .map(|m| m.as_str())
         .unwrap_or_default();
     
     let scope = captures.get(2).map(|m| m.as_str().to_string());
     
+    // Fall back to the default categories when no validator is configured
+    let default_validator = CommitTypeValidator::default();
+    
     // Check if this is an SVCMS-extended type
-    let is_valid = if let Some(validator) = validator {
-        validator.is_valid(&commit_type, scope.as_deref())
+    let type_check = if let Some(validator) = validator {
+        validator.check(raw_type, scope.as_deref())
+    } else if raw_type.contains('.') {
+        default_validator.check(raw_type, scope.as_deref())
+    } else if is_valid_svcms_type(raw_type) {
+        Ok(())
     } else {
-        is_valid_svcms_type(&commit_type)
+        Err(TypeRejection::UnknownType { commit_type: raw_type.to_string() })
     };
     
-    if !is_valid {
-        return Ok(None);
+    let type_validator = validator.unwrap_or(&default_validator);
+    
+    if let Err(rejection) = type_check {
+        let known_types: Vec<String> = match validator {
+            Some(validator) => validator.known_types(),
+            None => BUILTIN_TYPES.iter().map(|t| t.to_string()).collect(),
+        };
+        let mut kind = DiagnosticKind::from_rejection(rejection, &known_types, &type_validator.category_names());
+        
+        // Point at the category the type actually belongs to
+        if let DiagnosticKind::TypeNotInCategory { commit_type, suggestion, .. } = &mut kind {
+            *suggestion = type_validator.category_for(commit_type)
+                .map(|category| format!("{}.{}", category, commit_type));
+        }
+        
+        let span = match &kind {
+            DiagnosticKind::CategoryNotAllowed { scope, .. } => {
+                Span::new(1, raw_type.chars().count() + 2, scope.chars().count())
+            }
+            _ => Span::new(1, 1, raw_type.chars().count()),
+        };
+        
+        // List valid alternatives when there is no single close suggestion
+        let has_suggestion = matches!(
+            &kind,
+            DiagnosticKind::UnknownType { suggestion: Some(_), .. } |
+            DiagnosticKind::UnknownCategory { suggestion: Some(_), .. } |
+            DiagnosticKind::TypeNotInCategory { suggestion: Some(_), .. }
+        );
+        let mut diagnostic = Diagnostic::new(kind, span);
+        let alternatives = type_validator.suggest_alternatives(raw_type, scope.as_deref());
+        if !has_suggestion && !alternatives.is_empty() {
+            diagnostic = diagnostic.with_help(format!("valid alternatives: {}", alternatives.join(", ")));
+        }
+        diagnostics.push(diagnostic);
+        return ParseOutcome { commit: None, diagnostics };
     }
-    let summary = captures.get(3)
+    
+    // Split into category and type, resolving aliases when configured
+    let parsed_type = type_validator.parse_commit_type(raw_type);
+    let category = parsed_type.category
+        .or_else(|| type_validator.category_for(&parsed_type.commit_type));
+    let commit_type = parsed_type.commit_type;
+    
+    let summary = captures.get(4)
         .map(|m| m.as_str().to_string())
         .unwrap_or_default();
     
-    // Extract body (everything between header and footers)
-    let body = extract_body(&lines);
+    // Split body and trailer block
+    let (body, trailers) = split_trailers(&lines[1..]);
+    
+    // Route recognized trailers to their fields, keep the rest in order
+    let mut context = None;
+    let mut refs = Vec::new();
+    let mut memory = Vec::new();
+    let mut location = None;
+    let mut tags = Vec::new();
+    let mut breaking_change = None;
+    let mut co_authors = Vec::new();
+    let mut footers: IndexMap<String, Vec<String>> = IndexMap::new();
+    
+    for (i, trailer) in trailers.iter().enumerate() {
+        match known_token(&trailer.token) {
+            Some(KnownToken::Context) => {
+                context.get_or_insert_with(|| fold(&trailer.value));
+            }
+            Some(KnownToken::Refs) => {
+                refs.extend(split_list(&trailer.value).iter().map(|r| classify_reference(r)));
+            }
+            Some(KnownToken::Memory) => {
+                if trailer.value.is_empty() {
+                    diagnostics.push(Diagnostic::new(
+                        DiagnosticKind::EmptyMemory,
+                        trailer_span(trailer),
+                    ));
+                    continue;
+                }
+                // A Location directly after a memory belongs to it
+                let paired_location = trailers.get(i + 1)
+                    .filter(|next| known_token(&next.token) == Some(KnownToken::Location))
+                    .map(|next| fold(&next.value));
+                memory.push(MemoryEntry {
+                    content: trailer.value.clone(),
+                    location: paired_location,
+                });
+            }
+            Some(KnownToken::Location) => {
+                let value = fold(&trailer.value);
+                if location_escapes_repository(&value) {
+                    diagnostics.push(Diagnostic::new(
+                        DiagnosticKind::LocationOutsideRepository { location: value.clone() },
+                        trailer_value_span(trailer),
+                    ));
+                }
+                location.get_or_insert(value);
+            }
+            Some(KnownToken::Tags) => tags.extend(split_list(&trailer.value)),
+            Some(KnownToken::BreakingChange) => {
+                breaking_change.get_or_insert_with(|| trailer.value.clone());
+            }
+            Some(KnownToken::CoAuthoredBy) => co_authors.push(Person::parse(&fold(&trailer.value))),
+            None => {
+                footers.entry(trailer.token.clone())
+                    .or_default()
+                    .push(trailer.value.clone());
+            }
+        }
+    }
     
-    // Extract footers
-    let full_message = lines.join("\n");
-    let context = extract_field(&full_message, &CONTEXT_PATTERN);
-    let refs = extract_refs(&full_message);
-    let memory = extract_field(&full_message, &MEMORY_PATTERN);
-    let location = extract_field(&full_message, &LOCATION_PATTERN);
-    let tags = extract_tags(&full_message);
+    let breaking = captures.get(3).is_some() || breaking_change.is_some();
+    let cherry_picked_from = lines.iter().rev()
+        .find(|line| !line.trim().is_empty())
+        .and_then(|line| CHERRY_PICK_PATTERN.captures(line.trim()))
+        .map(|cap| cap[1].to_string());
     
-    Ok(Some(SvcmsCommit {
+    let commit = SvcmsCommit {
         sha: sha.to_string(),
+        full_sha: None,
+        category,
         commit_type,
         scope,
         summary,
+        breaking,
+        breaking_change,
         body,
         memory,
+        memory_offset: 0,
         location,
         context,
         refs,
         tags,
-        timestamp,
-    }))
+        footers,
+        timestamp: timestamp.into(),
+        reverted_by: None,
+        cherry_picked_from,
+        duplicates: Vec::new(),
+        author: None,
+        committer: None,
+        co_authors,
+        changed_files: None,
+    };
+    
+    ParseOutcome { commit: Some(commit), diagnostics }
 }
 
 /// Check if the commit type is valid according to SVCMS
 fn is_valid_svcms_type(commit_type: &str) -> bool {
-    matches!(
-        commit_type,
-        // Standard Conventional Commits
-        "feat" | "fix" | "fixed" | "docs" | "style" | "refactor" | 
-        "perf" | "test" | "build" | "ci" | "chore" |
-        // SVCMS Knowledge Types
-        "learned" | "insight" | "context" | "decision" | "decided" | "memory" |
-        // SVCMS Collaboration Types
-        "discussed" | "explored" | "attempted" |
-        // SVCMS Meta Types
-        "workflow" | "preference" | "pattern"
-    )
+    BUILTIN_TYPES.contains(&commit_type)
 }
 
-/// Extract the body content (between header and footers)
-fn extract_body(lines: &[&str]) -> Option<String> {
-    if lines.len() <= 1 {
-        return None;
+/// Types accepted when no `[commit_types]` config is present
+const BUILTIN_TYPES: &[&str] = &[
+    // Standard Conventional Commits
+    "feat", "fix", "fixed", "docs", "style", "refactor",
+    "perf", "test", "build", "ci", "chore",
+    // SVCMS Knowledge Types
+    "learned", "insight", "context", "decision", "decided", "memory",
+    // SVCMS Collaboration Types
+    "discussed", "explored", "attempted",
+    // SVCMS Meta Types
+    "workflow", "preference", "pattern",
+];
+
+/// A single git trailer: `Token: value` or `Token #value`
+///
+/// Folded continuation lines are kept as separate lines in `value`, with
+/// their common indentation removed. For the `#` form the `#` stays part of
+/// the value, so `Refs #87` yields `#87`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trailer {
+    pub token: String,
+    pub value: String,
+    /// Index of the trailer's first line in the lines given to `split_trailers`
+    pub line: usize,
+}
+
+/// Span of a trailer in the full message (whose header precedes the trailer lines)
+pub(crate) fn trailer_span(trailer: &Trailer) -> Span {
+    Span::new(trailer.line + 2, 1, trailer.token.chars().count() + 1)
+}
+
+/// Span of a trailer's (first line) value in the full message
+pub(crate) fn trailer_value_span(trailer: &Trailer) -> Span {
+    let first_line = trailer.value.lines().next().unwrap_or_default();
+    Span::new(trailer.line + 2, trailer.token.chars().count() + 3, first_line.chars().count())
+}
+
+/// Footer tokens SVCMS maps onto `SvcmsCommit` fields
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum KnownToken {
+    Context,
+    Refs,
+    Memory,
+    Location,
+    Tags,
+    BreakingChange,
+    CoAuthoredBy,
+}
+
+/// Match a trailer token against the SVCMS footers (case-insensitive like git,
+/// except `BREAKING CHANGE` which Conventional Commits requires in uppercase)
+fn known_token(token: &str) -> Option<KnownToken> {
+    if token == "BREAKING CHANGE" || token == "BREAKING-CHANGE" {
+        return Some(KnownToken::BreakingChange);
+    }
+    
+    match token.to_ascii_lowercase().as_str() {
+        "context" => Some(KnownToken::Context),
+        "ref" | "refs" => Some(KnownToken::Refs),
+        "memory" => Some(KnownToken::Memory),
+        "location" => Some(KnownToken::Location),
+        "tag" | "tags" => Some(KnownToken::Tags),
+        "co-authored-by" => Some(KnownToken::CoAuthoredBy),
+        _ => None,
+    }
+}
+
+/// Split the lines after the header into body and trailers
+///
+/// Follows git-interpret-trailers: only the last paragraph can be a trailer
+/// block. It qualifies when every line is a trailer or continuation, or when
+/// at least 25% of its lines are trailers and one of them is an SVCMS footer.
+pub fn split_trailers(lines: &[&str]) -> (Option<String>, Vec<Trailer>) {
+    // Ignore trailing blank lines and the line `git cherry-pick -x` appends,
+    // which may come after the trailers or in its own paragraph
+    let mut end = lines.iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    if end > 0 && CHERRY_PICK_PATTERN.is_match(lines[end - 1].trim()) {
+        end = lines[..end - 1].iter()
+            .rposition(|line| !line.trim().is_empty())
+            .map(|i| i + 1)
+            .unwrap_or(0);
     }
+    let lines = &lines[..end];
     
-    let mut body_lines = Vec::new();
-    let mut in_body = false;
+    // The last paragraph starts after the last blank line
+    let start = lines.iter()
+        .rposition(|line| line.trim().is_empty())
+        .map(|i| i + 1)
+        .unwrap_or(0);
     
-    for line in lines.iter().skip(1) {
-        // Empty line after header starts the body
-        if !in_body && line.trim().is_empty() {
-            in_body = true;
-            continue;
+    let trailers = parse_trailer_block(&lines[start..], start);
+    let (body_lines, trailers) = match trailers {
+        Some(trailers) => (&lines[..start], trailers),
+        None => (lines, Vec::new()),
+    };
+    
+    let body = body_lines.join("\n").trim().to_string();
+    let body = if body.is_empty() { None } else { Some(body) };
+    
+    (body, trailers)
+}
+
+/// Parse a paragraph as a trailer block, or `None` if it isn't one
+fn parse_trailer_block(paragraph: &[&str], offset: usize) -> Option<Vec<Trailer>> {
+    let mut trailers: Vec<(String, String, usize, Vec<&str>)> = Vec::new();
+    let mut non_trailer_lines = 0;
+    let mut in_trailer = false;
+    
+    for (i, line) in paragraph.iter().enumerate() {
+        if line.starts_with(char::is_whitespace) {
+            // Continuation of the previous trailer
+            if in_trailer {
+                if let Some((_, _, _, continuation)) = trailers.last_mut() {
+                    continuation.push(line);
+                }
+            } else {
+                non_trailer_lines += 1;
+            }
+        } else if let Some(cap) = TRAILER_PATTERN.captures(line) {
+            let token = cap[1].to_string();
+            let value = cap.get(2).or_else(|| cap.get(3))
+                .map(|m| m.as_str().trim().to_string())
+                .unwrap_or_default();
+            trailers.push((token, value, offset + i, Vec::new()));
+            in_trailer = true;
+        } else {
+            non_trailer_lines += 1;
+            in_trailer = false;
         }
-        
-        // Check if we've hit a footer
-        if in_body && is_footer_line(line) {
-            break;
+    }
+    
+    if trailers.is_empty() {
+        return None;
+    }
+    
+    if non_trailer_lines > 0 {
+        let mostly_trailers = trailers.len() * 3 >= non_trailer_lines;
+        let has_svcms_footer = trailers.iter().any(|(token, _, _, _)| known_token(token).is_some());
+        if !(mostly_trailers && has_svcms_footer) {
+            return None;
         }
+    }
+    
+    Some(trailers.into_iter()
+        .map(|(token, value, line, continuation)| Trailer {
+            token,
+            value: join_folded(&value, &continuation),
+            line,
+        })
+        .collect())
+}
+
+/// Join a trailer's first line with its continuation lines, removing the
+/// common indentation but keeping line breaks
+fn join_folded(first: &str, continuation: &[&str]) -> String {
+    let indent = continuation.iter()
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    
+    let mut value_lines: Vec<&str> = Vec::new();
+    if !first.is_empty() {
+        value_lines.push(first);
+    }
+    value_lines.extend(continuation.iter().map(|line| {
+        line.get(indent..).unwrap_or_else(|| line.trim_start()).trim_end()
+    }));
+    
+    value_lines.join("\n")
+}
+
+/// Fold a multi-line trailer value onto a single line
+pub(crate) fn fold(value: &str) -> String {
+    value.lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Find the commit a revert undoes, from its `This reverts commit <sha>` line
+///
+/// Works for the `Revert "..."` messages git generates as well as SVCMS
+/// `revert(scope): ...` commits that keep git's body line.
+pub fn reverted_commit(message: &str) -> Option<String> {
+    REVERT_PATTERN.captures(message).map(|cap| cap[1].to_string())
+}
+
+/// Split a squash or merge commit message into the messages it combines
+///
+/// Each `* type(scope): summary` bullet starts a message that runs until the
+/// next bullet, as GitHub writes squash merges. When the message's own header
+/// isn't an SVCMS one, the first body line may also be a plain header, since
+/// GitHub's merge commits put the PR title there.
+/// Trailers after a `---` separator line, like GitHub's `Co-authored-by`
+/// block, are shared by every message. Returns `None` when the body holds no
+/// such headers.
+pub fn split_squash_message(message: &str) -> Option<Vec<String>> {
+    let title_in_body = message.lines().next().is_some_and(|header| !HEADER_PATTERN.is_match(header));
+    let lines: Vec<&str> = message.lines().skip(1).collect();
+    let (lines, shared) = match lines.iter().position(|line| SQUASH_SEPARATOR_PATTERN.is_match(line.trim())) {
+        Some(separator) => (&lines[..separator], &lines[separator + 1..]),
+        None => (&lines[..], &[][..]),
+    };
+    let shared: Vec<&str> = shared.iter().copied().filter(|line| !line.trim().is_empty()).collect();
+    
+    // Every bullet ends the previous message; bullets that aren't SVCMS
+    // headers start messages that are dropped
+    let first_body_line = lines.iter().position(|line| !line.trim().is_empty()).filter(|_| title_in_body);
+    let mut sections: Vec<Option<Vec<&str>>> = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let header = match SQUASH_BULLET_PATTERN.captures(line) {
+            Some(cap) => cap.get(1).map(|m| m.as_str()),
+            None if Some(i) == first_body_line => Some(*line),
+            None => None,
+        };
         
-        if in_body {
-            body_lines.push(*line);
+        match header {
+            Some(header) if HEADER_PATTERN.is_match(header) => sections.push(Some(vec![header])),
+            Some(_) if SQUASH_BULLET_PATTERN.is_match(line) => sections.push(None),
+            _ => {
+                if let Some(Some(section)) = sections.last_mut() {
+                    section.push(line);
+                }
+            }
         }
     }
     
-    if body_lines.is_empty() {
-        None
-    } else {
-        Some(body_lines.join("\n").trim().to_string())
+    let sections: Vec<Vec<&str>> = sections.into_iter().flatten().collect();
+    if sections.is_empty() {
+        return None;
     }
+    
+    let messages = sections.into_iter()
+        .map(|section| {
+            // Bullet bodies are usually indented under the bullet
+            let body = &section[1..];
+            let indented = body.iter().all(|line| line.trim().is_empty() || line.starts_with("  "));
+            let mut message_lines: Vec<&str> = vec![section[0]];
+            message_lines.extend(body.iter().map(|line| if indented { line.get(2..).unwrap_or_default() } else { line }));
+            let mut message = message_lines.join("\n").trim_end().to_string();
+            
+            if !shared.is_empty() {
+                // Join the section's own trailer block when it has one
+                let (_, trailers) = split_trailers(&message_lines[1..]);
+                message.push_str(if trailers.is_empty() { "\n\n" } else { "\n" });
+                message.push_str(&shared.join("\n"));
+            }
+            
+            message
+        })
+        .collect();
+    
+    Some(messages)
 }
 
-/// Check if a line is a footer line
-fn is_footer_line(line: &str) -> bool {
-    line.starts_with("Context:") ||
-    line.starts_with("Refs:") ||
-    line.starts_with("Ref:") ||
-    line.starts_with("Memory:") ||
-    line.starts_with("Location:") ||
-    line.starts_with("Tags:") ||
-    line.starts_with("Tag:")
+/// Check if a git note starts with its own SVCMS header rather than a trailer
+pub fn note_has_header(note: &str) -> bool {
+    let mut lines = note.lines();
+    let header = lines.next().unwrap_or_default();
+    let token = header.split(':').next().unwrap_or_default();
+    
+    HEADER_PATTERN.is_match(header) &&
+        known_token(token).is_none() &&
+        lines.next().is_none_or(|line| line.trim().is_empty())
 }
 
-/// Extract a single field value from the message
-fn extract_field(message: &str, pattern: &Regex) -> Option<String> {
-    pattern.captures(message)
-        .and_then(|cap| cap.get(1))
-        .map(|m| m.as_str().trim().to_string())
+/// Merge the trailers of a git note into a parsed commit
+///
+/// The note holds a trailer block like a commit's own, optionally under an
+/// SVCMS header, which is ignored here. Memories, refs, tags, co-authors and
+/// other footers are added; context, location and breaking change only fill
+/// in what the commit leaves empty.
+pub fn merge_note(commit: &mut SvcmsCommit, note: &str) -> Result<()> {
+    let trailers = if note_has_header(note) {
+        note.lines().skip(1).collect::<Vec<_>>().join("\n")
+    } else {
+        note.to_string()
+    };
+    
+    // Route the trailers through the parser under a stand-in header
+    let Some(annotation) = parse_commit_message(&commit.sha, &format!("learned: note\n\n{}", trailers), commit.timestamp)? else {
+        return Ok(());
+    };
+    
+    commit.memory.extend(annotation.memory);
+    commit.refs.extend(annotation.refs.into_iter().filter(|r| !commit.refs.contains(r)).collect::<Vec<_>>());
+    for tag in annotation.tags {
+        if !commit.tags.contains(&tag) {
+            commit.tags.push(tag);
+        }
+    }
+    commit.co_authors.extend(annotation.co_authors);
+    for (token, values) in annotation.footers {
+        commit.footers.entry(token).or_default().extend(values);
+    }
+    
+    if commit.context.is_none() {
+        commit.context = annotation.context;
+    }
+    if commit.location.is_none() {
+        commit.location = annotation.location;
+    }
+    if commit.breaking_change.is_none() && annotation.breaking_change.is_some() {
+        commit.breaking_change = annotation.breaking_change;
+        commit.breaking = true;
+    }
+    
+    Ok(())
 }
 
-/// Extract references as a vector
-fn extract_refs(message: &str) -> Vec<String> {
-    extract_field(message, &REFS_PATTERN)
-        .map(|refs_str| {
-            refs_str.split(',')
-                .map(|r| r.trim().to_string())
-                .filter(|r| !r.is_empty())
-                .collect()
-        })
-        .unwrap_or_default()
+/// Classify a `Refs:` entry by what it points at
+pub fn classify_reference(reference: &str) -> Reference {
+    let reference = reference.trim();
+    
+    if let Some(cap) = ISSUE_REF_PATTERN.captures(reference) {
+        if let Ok(number) = cap[1].parse() {
+            return Reference::Issue { number };
+        }
+    }
+    
+    if reference.starts_with("http://") || reference.starts_with("https://") {
+        return Reference::Url { url: reference.to_string() };
+    }
+    
+    // Hex strings with a digit; all-letter hex like "defaced" is more likely a word
+    if COMMIT_REF_PATTERN.is_match(reference) && reference.chars().any(|c| c.is_ascii_digit()) {
+        return Reference::Commit { sha: reference.to_string() };
+    }
+    
+    if !reference.contains(char::is_whitespace) {
+        let (path, line) = match FILE_LINE_PATTERN.captures(reference) {
+            Some(cap) => (
+                cap[1].to_string(),
+                cap.get(2).or_else(|| cap.get(3)).and_then(|m| m.as_str().parse().ok()),
+            ),
+            None => (reference.to_string(), None),
+        };
+        
+        if path.contains('/') || FILE_EXTENSION_PATTERN.is_match(&path) {
+            return Reference::File { path, line };
+        }
+    }
+    
+    Reference::Text { text: reference.to_string() }
 }
 
-/// Extract tags as a vector
-fn extract_tags(message: &str) -> Vec<String> {
-    extract_field(message, &TAGS_PATTERN)
-        .map(|tags_str| {
-            tags_str.split(',')
-                .map(|t| t.trim().to_string())
-                .filter(|t| !t.is_empty())
-                .collect()
-        })
-        .unwrap_or_default()
+/// Split a comma-separated trailer value (refs, tags) into items
+fn split_list(value: &str) -> Vec<String> {
+    fold(value)
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
 }
 
 #[cfg(test)]
@@ -237,12 +671,320 @@ Tags: api, rate-limiting, retry-strategy"#;
         assert_eq!(commit.summary, "rate limiting resets at minute boundaries");
         assert!(commit.body.is_some());
         assert_eq!(commit.context, Some("Staff Scheduling API integration".to_string()));
-        assert_eq!(commit.memory, Some("API rate limit resets at :00 seconds of each minute".to_string()));
-        assert_eq!(commit.location, Some("src/api/CLAUDE.md".to_string()));
+        assert_eq!(commit.memory.len(), 1);
+        assert_eq!(commit.memory[0].content, "API rate limit resets at :00 seconds of each minute");
+        assert_eq!(commit.memory[0].location, Some("src/api/CLAUDE.md".to_string()));
+        assert_eq!(commit.refs, vec![
+            Reference::Issue { number: 87 },
+            Reference::File { path: "src/api/client.ts".to_string(), line: None },
+        ]);
         assert_eq!(commit.refs.len(), 2);
         assert_eq!(commit.tags.len(), 3);
     }
     
+    #[test]
+    fn test_parse_two_tier_commit() {
+        let message = "knowledge.learned(auth): JWT tokens expire after 24 hours\n\nMemory: JWT expiry is 24h";
+        let commit = parse_commit_message("abc123", message, Utc::now()).unwrap().unwrap();
+        
+        assert_eq!(commit.category, Some("knowledge".to_string()));
+        assert_eq!(commit.commit_type, "learned");
+        assert_eq!(commit.scope, Some("auth".to_string()));
+        assert_eq!(commit.summary, "JWT tokens expire after 24 hours");
+        assert_eq!(commit.memory[0].content, "JWT expiry is 24h");
+        
+        // Single-tier types get their category inferred
+        let commit = parse_commit_message("abc124", "feat(api): add endpoint", Utc::now()).unwrap().unwrap();
+        assert_eq!(commit.category, Some("standard".to_string()));
+        
+        // Mismatched category and type is rejected
+        let result = parse_commit_message("abc125", "knowledge.feat(api): nope", Utc::now()).unwrap();
+        assert!(result.is_none());
+    }
+    
+    #[test]
+    fn test_parse_with_configured_validator() {
+        let config: crate::config::CommitTypesConfig = toml::from_str(r#"
+[aliases]
+fixed = "fix"
+
+[scopes.modules]
+auth = { categories = ["standard"], custom_types = ["integrated"] }
+"#).unwrap();
+        let validator = CommitTypeValidator::from_config(&config);
+        
+        let commit = parse_commit_message_with_validator("a1", "fixed(api): typo", Utc::now(), Some(&validator))
+            .unwrap().unwrap();
+        assert_eq!(commit.commit_type, "fix");
+        assert_eq!(commit.category, Some("standard".to_string()));
+        
+        let commit = parse_commit_message_with_validator("a2", "auth.integrated(oauth): Google provider", Utc::now(), Some(&validator))
+            .unwrap().unwrap();
+        assert_eq!(commit.category, Some("auth".to_string()));
+        assert_eq!(commit.commit_type, "integrated");
+        
+        // The auth scope only allows standard types
+        let result = parse_commit_message_with_validator("a3", "learned(auth): nope", Utc::now(), Some(&validator)).unwrap();
+        assert!(result.is_none());
+    }
+    
+    #[test]
+    fn test_parse_breaking_change() {
+        let commit = parse_commit_message("b1", "feat(api)!: drop v1 endpoints", Utc::now()).unwrap().unwrap();
+        assert!(commit.breaking);
+        assert_eq!(commit.scope, Some("api".to_string()));
+        assert_eq!(commit.summary, "drop v1 endpoints");
+        assert!(commit.breaking_change.is_none());
+        
+        let message = r#"refactor(auth): rename session helpers
+
+Session helpers now live in the auth module.
+
+BREAKING CHANGE: `get_session` is now `auth::session`
+Memory: Session helpers moved to auth"#;
+        let commit = parse_commit_message("b2", message, Utc::now()).unwrap().unwrap();
+        assert!(commit.breaking);
+        assert_eq!(commit.breaking_change, Some("`get_session` is now `auth::session`".to_string()));
+        assert_eq!(commit.body, Some("Session helpers now live in the auth module.".to_string()));
+        
+        let commit = parse_commit_message("b3", "fix: typo\n\nBREAKING-CHANGE: config key renamed", Utc::now()).unwrap().unwrap();
+        assert!(commit.breaking);
+        
+        let commit = parse_commit_message("b4", "fix(api): typo", Utc::now()).unwrap().unwrap();
+        assert!(!commit.breaking);
+    }
+    
+    #[test]
+    fn test_parse_multiple_memories() {
+        let message = r#"learned(http): client retries and timeouts
+
+Context: Flaky integration tests
+Memory: Retries only apply to idempotent requests
+Location: src/http/CLAUDE.md
+Memory: Build clients with explicit timeouts:
+    Client::builder()
+        .timeout(Duration::from_secs(5))
+Memory: Connection pool is shared across
+  all clients in the process
+Tags: http"#;
+        
+        let commit = parse_commit_message("m1", message, Utc::now()).unwrap().unwrap();
+        assert_eq!(commit.memory.len(), 3);
+        assert_eq!(commit.memory[0].content, "Retries only apply to idempotent requests");
+        assert_eq!(commit.memory[0].location, Some("src/http/CLAUDE.md".to_string()));
+        assert_eq!(
+            commit.memory[1].content,
+            "Build clients with explicit timeouts:\nClient::builder()\n    .timeout(Duration::from_secs(5))"
+        );
+        assert_eq!(commit.memory[1].location, None);
+        assert_eq!(commit.memory[2].content, "Connection pool is shared across\nall clients in the process");
+        assert_eq!(commit.tags, vec!["http".to_string()]);
+    }
+    
+    #[test]
+    fn test_trailers_only_from_last_paragraph() {
+        let message = r#"docs(spec): explain footers
+
+Write footers like this:
+Memory: this line is part of the body
+
+Memory: the real memory
+Co-authored-by: Ada <ada@example.com>
+Signed-off-by: Grace <grace@example.com>
+Co-authored-by: Linus <linus@example.com>
+Fixes #42
+Reviewed-by: Ken
+  Thompson"#;
+        
+        let commit = parse_commit_message("t1", message, Utc::now()).unwrap().unwrap();
+        assert_eq!(commit.body, Some("Write footers like this:\nMemory: this line is part of the body".to_string()));
+        assert_eq!(commit.memory.len(), 1);
+        assert_eq!(commit.memory[0].content, "the real memory");
+        
+        let tokens: Vec<&str> = commit.footers.keys().map(|k| k.as_str()).collect();
+        assert_eq!(tokens, vec!["Signed-off-by", "Fixes", "Reviewed-by"]);
+        assert_eq!(commit.co_authors, vec![
+            Person::new("Ada", Some("ada@example.com".to_string())),
+            Person::new("Linus", Some("linus@example.com".to_string())),
+        ]);
+        assert_eq!(commit.footers["Fixes"], vec!["#42".to_string()]);
+        assert_eq!(commit.footers["Reviewed-by"], vec!["Ken\nThompson".to_string()]);
+    }
+    
+    #[test]
+    fn test_split_trailers_requires_trailer_paragraph() {
+        // A prose last paragraph is body, not trailers
+        let lines = vec!["", "First paragraph.", "", "Note that this: is prose", "spanning lines."];
+        let (body, trailers) = split_trailers(&lines);
+        assert!(trailers.is_empty());
+        assert_eq!(body, Some("First paragraph.\n\nNote that this: is prose\nspanning lines.".to_string()));
+        
+        // Mostly trailers with an SVCMS footer still qualifies
+        let lines = vec!["", "Memory: keep it", "(see discussion)", "Refs #7"];
+        let (body, trailers) = split_trailers(&lines);
+        assert!(body.is_none());
+        assert_eq!(trailers.len(), 2);
+        assert_eq!(trailers[1], Trailer { token: "Refs".to_string(), value: "#7".to_string(), line: 3 });
+    }
+    
+    #[test]
+    fn test_diagnostics_explain_rejections() {
+        let outcome = parse_commit_message_with_diagnostics("d1", "learnd(api): typo in type", Utc::now(), None);
+        assert!(outcome.commit.is_none());
+        assert_eq!(outcome.diagnostics.len(), 1);
+        assert_eq!(outcome.diagnostics[0].to_string(), "1:1: error: unknown type `learnd`, did you mean `learned`");
+        
+        let outcome = parse_commit_message_with_diagnostics("d2", "just some text", Utc::now(), None);
+        assert_eq!(outcome.diagnostics[0].kind, DiagnosticKind::MalformedHeader);
+        
+        let outcome = parse_commit_message_with_diagnostics("d3", "knowledge.feat(api): wrong category", Utc::now(), None);
+        assert_eq!(
+            outcome.diagnostics[0].to_string(),
+            "1:1: error: category `knowledge` has no type `feat`, did you mean `standard.feat`"
+        );
+        
+        let config: crate::config::CommitTypesConfig = toml::from_str(r#"
+[scopes.modules]
+auth = { categories = ["standard"], custom_types = [] }
+"#).unwrap();
+        let validator = CommitTypeValidator::from_config(&config);
+        let outcome = parse_commit_message_with_diagnostics("d4", "meta.workflow(auth): nope", Utc::now(), Some(&validator));
+        assert_eq!(outcome.diagnostics[0].span, Span::new(1, 15, 4));
+        assert_eq!(outcome.diagnostics[0].to_string(), "1:15: error: scope `auth` does not allow category `meta`");
+    }
+    
+    #[test]
+    fn test_diagnostics_warnings_keep_commit() {
+        let message = "learned(api): footers\n\nMemory:\nMemory: real one\nLocation: ../elsewhere/CLAUDE.md";
+        let outcome = parse_commit_message_with_diagnostics("w1", message, Utc::now(), None);
+        
+        assert!(!outcome.has_errors());
+        assert_eq!(outcome.commit.unwrap().memory.len(), 1);
+        assert_eq!(outcome.diagnostics.len(), 2);
+        assert_eq!(outcome.diagnostics[0].to_string(), "3:1: warning: Memory footer is empty");
+        assert_eq!(
+            outcome.diagnostics[1].to_string(),
+            "5:11: warning: Location `../elsewhere/CLAUDE.md` is outside the repository"
+        );
+    }
+    
+    #[test]
+    fn test_reverted_commit() {
+        let sha = "4f1c2e3a9b8d7c6e5f4a3b2c1d0e9f8a7b6c5d4e";
+        let message = format!("Revert \"learned(api): retry on 429\"\n\nThis reverts commit {}.\n", sha);
+        assert_eq!(reverted_commit(&message), Some(sha.to_string()));
+        
+        let message = "revert(api): retry on 429\n\nThis reverts commit 4f1c2e3.\n\nMemory: Retries made it worse";
+        assert_eq!(reverted_commit(message), Some("4f1c2e3".to_string()));
+        
+        assert_eq!(reverted_commit("fix(api): handle 429\n\nMentions This reverts commit abc1234 mid-line"), None);
+    }
+    
+    #[test]
+    fn test_cherry_picked_from() {
+        let sha = "4f1c2e3a9b8d7c6e5f4a3b2c1d0e9f8a7b6c5d4e";
+        
+        // Own paragraph: the memory paragraph above is still the trailer block
+        let message = format!("learned(api): retry on 429\n\nMemory: Back off until the reset\n\n(cherry picked from commit {})", sha);
+        let commit = parse_commit_message("abc1234", &message, Utc::now()).unwrap().unwrap();
+        assert_eq!(commit.cherry_picked_from, Some(sha.to_string()));
+        assert_eq!(commit.memory.len(), 1);
+        assert_eq!(commit.body, None);
+        
+        // Appended to the trailer block
+        let message = format!("learned(api): retry on 429\n\nMemory: Back off until the reset\n(cherry picked from commit {})", sha);
+        let commit = parse_commit_message("abc1234", &message, Utc::now()).unwrap().unwrap();
+        assert_eq!(commit.cherry_picked_from, Some(sha.to_string()));
+        assert!(commit.footers.is_empty());
+    }
+    
+    #[test]
+    fn test_split_squash_message() {
+        let message = [
+            "Add rate limiting (#42)",
+            "",
+            "* feat(api): add rate limiter",
+            "",
+            "Memory: Limits reset at minute boundaries",
+            "",
+            "* fix(api): off-by-one in window",
+            "",
+            "  Details in the issue.",
+            "",
+            "  Memory: Windows are half-open",
+            "",
+            "* wip",
+            "",
+            "---------",
+            "",
+            "Co-authored-by: Ada <ada@example.com>",
+        ].join("\n");
+        
+        let messages = split_squash_message(&message).unwrap();
+        assert_eq!(messages, vec![
+            "feat(api): add rate limiter\n\nMemory: Limits reset at minute boundaries\nCo-authored-by: Ada <ada@example.com>",
+            "fix(api): off-by-one in window\n\nDetails in the issue.\n\nMemory: Windows are half-open\nCo-authored-by: Ada <ada@example.com>",
+        ]);
+        
+        // GitHub merge commits carry the PR title as the first body line
+        let messages = split_squash_message("Merge pull request #7 from acme/retry\n\nfix(api): retry on 429\n\nMemory: Back off").unwrap();
+        assert_eq!(messages, vec!["fix(api): retry on 429\n\nMemory: Back off"]);
+        
+        assert_eq!(split_squash_message("feat(api): add endpoint\n\n- plain bullet\n- another"), None);
+        assert_eq!(split_squash_message("feat(api): add endpoint\n\ndocs: see the README"), None);
+    }
+    
+    #[test]
+    fn test_merge_note() {
+        let mut commit = parse_commit_message(
+            "abc1234",
+            "fix(api): handle 429\n\nRefs: #12\nTags: api",
+            Utc::now(),
+        ).unwrap().unwrap();
+        
+        merge_note(&mut commit, "Memory: Retry after the Retry-After header\nLocation: src/api/CLAUDE.md\nRefs: #12, #13\nTags: api, retry").unwrap();
+        assert_eq!(commit.memory, vec![MemoryEntry {
+            content: "Retry after the Retry-After header".to_string(),
+            location: Some("src/api/CLAUDE.md".to_string()),
+        }]);
+        assert_eq!(commit.location.as_deref(), Some("src/api/CLAUDE.md"));
+        assert_eq!(commit.refs, vec![Reference::Issue { number: 12 }, Reference::Issue { number: 13 }]);
+        assert_eq!(commit.tags, vec!["api", "retry"]);
+        
+        assert!(!note_has_header("Memory: one\nTags: x"));
+        assert!(note_has_header("learned(db): other\n\nMemory: Second insight"));
+        
+        // A header in the note is ignored when the commit has its own
+        merge_note(&mut commit, "learned(db): other\n\nMemory: Second insight").unwrap();
+        assert_eq!(commit.memory.len(), 2);
+        assert_eq!(commit.summary, "handle 429");
+    }
+    
+    #[test]
+    fn test_classify_reference() {
+        assert_eq!(classify_reference("#87"), Reference::Issue { number: 87 });
+        assert_eq!(
+            classify_reference("src/api/client.ts"),
+            Reference::File { path: "src/api/client.ts".to_string(), line: None }
+        );
+        assert_eq!(
+            classify_reference("src/api/client.ts:42"),
+            Reference::File { path: "src/api/client.ts".to_string(), line: Some(42) }
+        );
+        assert_eq!(
+            classify_reference("README.md#L7"),
+            Reference::File { path: "README.md".to_string(), line: Some(7) }
+        );
+        assert_eq!(classify_reference("a1b2c3d"), Reference::Commit { sha: "a1b2c3d".to_string() });
+        assert_eq!(
+            classify_reference("https://example.com/rfc"),
+            Reference::Url { url: "https://example.com/rfc".to_string() }
+        );
+        assert_eq!(classify_reference("defaced"), Reference::Text { text: "defaced".to_string() });
+        assert_eq!(classify_reference("v1.2.0"), Reference::Text { text: "v1.2.0".to_string() });
+        assert_eq!(classify_reference("design meeting"), Reference::Text { text: "design meeting".to_string() });
+    }
+    
     #[test]
     fn test_parse_non_svcms_commit() {
         let message = "random commit message without proper format";
@@ -265,6 +1007,7 @@ Tags: architecture, events"#;
         assert_eq!(commit.commit_type, "decided");
         assert_eq!(commit.scope, Some("architecture".to_string()));
         assert!(commit.body.is_none()); // No body, just footers
-        assert_eq!(commit.memory, Some("All state changes through events".to_string()));
+        assert_eq!(commit.memory[0].content, "All state changes through events");
+        assert_eq!(commit.memory[0].location, None);
     }
 }
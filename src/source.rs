@@ -0,0 +1,671 @@
+//! Where commits come from: a repository, or exports that need no clone
+
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::Command;
+use anyhow::{Result, Context, anyhow};
+use chrono::{DateTime, FixedOffset, Utc};
+use regex::Regex;
+use serde::Deserialize;
+use crate::{ChangeStatus, FileChange, Person, SvcmsCommit};
+use crate::commit_types::CommitTypeValidator;
+use crate::git::{self, Revisions};
+use crate::parser::{parse_commit_message_with_validator, reverted_commit};
+
+lazy_static::lazy_static! {
+    // First line of each message in an mbox; format-patch puts the commit SHA there
+    static ref MBOX_FROM_PATTERN: Regex = Regex::new(r"^From (\S+) ").unwrap();
+
+    // Subject prefix format-patch adds: "[PATCH]", "[PATCH v2 1/3]", "[RFC PATCH]"
+    static ref PATCH_PREFIX_PATTERN: Regex = Regex::new(r"^\[[^\]]*PATCH[^\]]*\]\s*").unwrap();
+
+    // RFC 2047 Q-encoded word, as git writes non-ASCII headers
+    static ref ENCODED_WORD_PATTERN: Regex = Regex::new(r"=\?[^?]+\?[qQ]\?([^?]*)\?=").unwrap();
+
+    static ref FULL_SHA_PATTERN: Regex = Regex::new(r"^[0-9a-f]{40}$").unwrap();
+}
+
+/// A source of SVCMS commits for the sync pipeline
+pub trait CommitSource {
+    /// Read the SVCMS commits, newest first, with reverted ones marked
+    fn commits(&self, validator: Option<&CommitTypeValidator>) -> Result<Vec<SvcmsCommit>>;
+    
+    /// What is being read, for progress output
+    fn describe(&self) -> String;
+}
+
+/// Open the source named on the command line
+///
+/// `-` reads a `git log` stream or NDJSON from stdin. A directory is read as a
+/// `git format-patch` series, a `.bundle` file (or one starting with git's
+/// bundle signature) as a git bundle, and any other file as an mbox.
+pub fn open_source(spec: &str) -> Result<Box<dyn CommitSource>> {
+    if spec == "-" {
+        return Ok(Box::new(LogSource::from_reader(std::io::stdin())?));
+    }
+    
+    let path = PathBuf::from(spec);
+    if path.is_dir() {
+        return Ok(Box::new(MboxSource::new(path)));
+    }
+    
+    let mut signature = [0u8; 16];
+    let read = fs::File::open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?
+        .read(&mut signature)?;
+    let is_bundle = path.extension().is_some_and(|ext| ext == "bundle") ||
+        signature[..read].starts_with(b"# v2 git bundle") ||
+        signature[..read].starts_with(b"# v3 git bundle");
+    
+    if is_bundle {
+        Ok(Box::new(BundleSource::new(path)))
+    } else {
+        Ok(Box::new(MboxSource::new(path)))
+    }
+}
+
+/// Commits walked in a local repository
+pub struct RepositorySource {
+    repo_path: String,
+    revisions: Revisions,
+    depth: usize,
+}
+
+impl RepositorySource {
+    pub fn new(repo_path: impl Into<String>, revisions: Revisions, depth: usize) -> Self {
+        RepositorySource { repo_path: repo_path.into(), revisions, depth }
+    }
+}
+
+impl CommitSource for RepositorySource {
+    fn commits(&self, validator: Option<&CommitTypeValidator>) -> Result<Vec<SvcmsCommit>> {
+        git::get_svcms_commits_with_validator(&self.repo_path, &self.revisions, self.depth, validator)
+    }
+    
+    fn describe(&self) -> String {
+        match self.depth {
+            usize::MAX => "all commits".to_string(),
+            depth => format!("{} commits", depth),
+        }
+    }
+}
+
+/// A commit as exported, before SVCMS parsing
+#[derive(Debug, Clone, Default, PartialEq)]
+struct RawCommit {
+    sha: String,
+    message: String,
+    timestamp: Option<DateTime<FixedOffset>>,
+    author: Option<Person>,
+    changed_files: Option<Vec<FileChange>>,
+}
+
+/// Parse exported commits, newest first, linking reverts within the export
+fn parse_raw_commits(raw_commits: Vec<RawCommit>, validator: Option<&CommitTypeValidator>) -> Result<Vec<SvcmsCommit>> {
+    let mut commits = Vec::new();
+    let mut reverts: Vec<(String, String)> = Vec::new();
+    
+    for raw in raw_commits {
+        let reverted_by = reverts.iter()
+            .position(|(target, _)| raw.sha.starts_with(target.as_str()) || target.starts_with(raw.sha.as_str()))
+            .map(|i| reverts.remove(i).1);
+        
+        let sha: String = raw.sha.chars().take(7).collect();
+        // A revert that was itself reverted doesn't retract anything
+        if reverted_by.is_none() {
+            if let Some(target) = reverted_commit(&raw.message) {
+                reverts.push((target, sha.clone()));
+            }
+        }
+        
+        let timestamp = raw.timestamp.unwrap_or_else(|| Utc::now().fixed_offset());
+        if let Some(mut commit) = parse_commit_message_with_validator(&sha, &raw.message, timestamp, validator)? {
+            commit.full_sha = (raw.sha.len() == 40).then(|| raw.sha.clone());
+            commit.author = raw.author;
+            commit.changed_files = raw.changed_files;
+            commit.reverted_by = reverted_by;
+            commits.push(commit);
+        }
+    }
+    
+    Ok(commits)
+}
+
+/// Patches from `git format-patch`: one mbox file, or a directory of `.patch` files
+pub struct MboxSource {
+    path: PathBuf,
+}
+
+impl MboxSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        MboxSource { path: path.into() }
+    }
+    
+    /// Read every message, newest first
+    fn raw_commits(&self) -> Result<Vec<RawCommit>> {
+        let mut files = Vec::new();
+        if self.path.is_dir() {
+            for entry in fs::read_dir(&self.path)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext == "patch" || ext == "eml" || ext == "mbox") {
+                    files.push(path);
+                }
+            }
+            // format-patch numbers the series oldest first
+            files.sort();
+        } else {
+            files.push(self.path.clone());
+        }
+        
+        let mut raw_commits = Vec::new();
+        for file in files {
+            let content = fs::read_to_string(&file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            raw_commits.extend(parse_mbox(&content));
+        }
+        
+        raw_commits.reverse();
+        Ok(raw_commits)
+    }
+}
+
+impl CommitSource for MboxSource {
+    fn commits(&self, validator: Option<&CommitTypeValidator>) -> Result<Vec<SvcmsCommit>> {
+        parse_raw_commits(self.raw_commits()?, validator)
+    }
+    
+    fn describe(&self) -> String {
+        format!("patches from {}", self.path.display())
+    }
+}
+
+/// Split an mbox into its messages, in file order
+fn parse_mbox(content: &str) -> Vec<RawCommit> {
+    let mut messages: Vec<Vec<&str>> = Vec::new();
+    let mut previous_blank = true;
+    
+    for line in content.lines() {
+        if previous_blank && MBOX_FROM_PATTERN.is_match(line) {
+            messages.push(Vec::new());
+        }
+        if let Some(message) = messages.last_mut() {
+            message.push(line);
+        }
+        previous_blank = line.trim().is_empty();
+    }
+    
+    // A lone patch saved without the mbox "From " line
+    if messages.is_empty() && content.lines().any(|line| line.starts_with("Subject:")) {
+        messages.push(content.lines().collect());
+    }
+    
+    messages.iter().map(|lines| parse_mail(lines)).collect()
+}
+
+/// Parse one format-patch mail into a commit
+fn parse_mail(lines: &[&str]) -> RawCommit {
+    let mut raw = RawCommit::default();
+    let mut lines = lines.iter().copied().peekable();
+    
+    if let Some(cap) = lines.peek().and_then(|line| MBOX_FROM_PATTERN.captures(line)) {
+        if FULL_SHA_PATTERN.is_match(&cap[1]) {
+            raw.sha = cap[1].to_string();
+        }
+        lines.next();
+    }
+    
+    // Headers, unfolding continuation lines
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in lines.by_ref() {
+        if line.is_empty() {
+            break;
+        }
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    let header = |name: &str| headers.iter().find(|(n, _)| n == name).map(|(_, value)| decode_header(value));
+    
+    let subject = header("subject").unwrap_or_default();
+    let subject = PATCH_PREFIX_PATTERN.replace(&subject, "").to_string();
+    raw.author = header("from").map(|from| Person::parse(&from));
+    raw.timestamp = header("date").and_then(|date| DateTime::parse_from_rfc2822(&date).ok());
+    
+    // The message ends at the `---` before the diffstat, or at the diff itself
+    let rest: Vec<&str> = lines.collect();
+    let body_end = rest.iter()
+        .position(|line| *line == "---" || line.starts_with("diff --git "))
+        .unwrap_or(rest.len());
+    let body = rest[..body_end].join("\n");
+    let body = body.trim();
+    
+    raw.message = if body.is_empty() {
+        subject
+    } else {
+        format!("{}\n\n{}", subject, body)
+    };
+    raw.changed_files = Some(parse_diff(&rest[body_end..]));
+    
+    if raw.sha.is_empty() {
+        raw.sha = content_sha(&raw.message);
+    }
+    
+    raw
+}
+
+/// Decode RFC 2047 Q-encoded words, as git writes non-ASCII names and subjects
+fn decode_header(value: &str) -> String {
+    // Whitespace between adjacent encoded words isn't part of the text
+    let joined = value.replace("?= =?", "?==?");
+    ENCODED_WORD_PATTERN.replace_all(&joined, |cap: &regex::Captures| {
+        let mut bytes = Vec::new();
+        let mut chars = cap[1].bytes();
+        while let Some(byte) = chars.next() {
+            match byte {
+                b'_' => bytes.push(b' '),
+                b'=' => {
+                    let hex: Vec<u8> = chars.by_ref().take(2).collect();
+                    match std::str::from_utf8(&hex).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                        Some(decoded) => bytes.push(decoded),
+                        None => bytes.extend(&hex),
+                    }
+                }
+                _ => bytes.push(byte),
+            }
+        }
+        String::from_utf8_lossy(&bytes).to_string()
+    }).to_string()
+}
+
+/// Files changed by a patch, from its `diff --git` sections
+fn parse_diff(lines: &[&str]) -> Vec<FileChange> {
+    let mut files: Vec<FileChange> = Vec::new();
+    let mut in_hunk = false;
+    
+    for line in lines {
+        if let Some(paths) = line.strip_prefix("diff --git ") {
+            let (old_path, new_path) = match paths.split_once(" b/") {
+                Some((old, new)) => (old.trim_start_matches("a/").to_string(), new.to_string()),
+                None => (paths.to_string(), paths.to_string()),
+            };
+            files.push(FileChange {
+                path: new_path,
+                old_path: Some(old_path),
+                status: ChangeStatus::Modified,
+                insertions: 0,
+                deletions: 0,
+            });
+            in_hunk = false;
+            continue;
+        }
+        let Some(file) = files.last_mut() else { continue };
+        
+        if line.starts_with("@@") {
+            in_hunk = true;
+        } else if in_hunk && line.starts_with('+') {
+            file.insertions += 1;
+        } else if in_hunk && line.starts_with('-') {
+            // `-- ` closes the mail with git's version signature
+            if line.trim_end() == "--" {
+                in_hunk = false;
+            } else {
+                file.deletions += 1;
+            }
+        } else if !in_hunk {
+            if line.starts_with("new file mode") {
+                file.status = ChangeStatus::Added;
+            } else if line.starts_with("deleted file mode") {
+                file.status = ChangeStatus::Deleted;
+                file.path = file.old_path.clone().unwrap_or_default();
+            } else if line.starts_with("rename from ") {
+                file.status = ChangeStatus::Renamed;
+            } else if line.starts_with("copy from ") {
+                file.status = ChangeStatus::Copied;
+            }
+        }
+    }
+    
+    for file in &mut files {
+        if !matches!(file.status, ChangeStatus::Renamed | ChangeStatus::Copied) {
+            file.old_path = None;
+        }
+    }
+    
+    files
+}
+
+/// Stand-in SHA for messages exported without one
+///
+/// The message's git blob hash, cut to 16 digits so it can't pass for a
+/// commit SHA. It ends up in hidden IDs, so it must not change between builds.
+fn content_sha(message: &str) -> String {
+    let oid = git2::Oid::hash_object(git2::ObjectType::Blob, message.as_bytes())
+        .expect("hashing in memory doesn't fail");
+    oid.to_string()[..16].to_string()
+}
+
+/// A complete `git bundle`, unpacked into a temporary bare repository
+///
+/// Needs the `git` command, since libgit2 can't read bundles.
+pub struct BundleSource {
+    path: PathBuf,
+}
+
+impl BundleSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        BundleSource { path: path.into() }
+    }
+}
+
+/// Temporary directory removed when dropped
+struct TempRepository(PathBuf);
+
+impl Drop for TempRepository {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Run git, failing with its stderr
+fn run_git(args: &[&std::ffi::OsStr]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .context("Failed to run git (needed to read bundles)")?;
+    if !output.status.success() {
+        return Err(anyhow!("git {}: {}",
+            args.first().map(|a| a.to_string_lossy()).unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+impl CommitSource for BundleSource {
+    fn commits(&self, validator: Option<&CommitTypeValidator>) -> Result<Vec<SvcmsCommit>> {
+        let bundle = fs::canonicalize(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        let nanos = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let dir = TempRepository(std::env::temp_dir().join(format!("synaptic-bundle-{}-{}", std::process::id(), nanos)));
+        
+        run_git(&["init".as_ref(), "--quiet".as_ref(), "--bare".as_ref(), dir.0.as_os_str()])?;
+        
+        // Fetch every head the bundle lists; a bare `HEAD` gets a ref of its own
+        let heads = run_git(&["bundle".as_ref(), "list-heads".as_ref(), bundle.as_os_str()])?;
+        let refspecs: Vec<String> = heads.lines()
+            .filter_map(|line| line.split_once(' ').map(|(_, name)| name.trim()))
+            .map(|name| match name.starts_with("refs/") {
+                true => format!("+{}:{}", name, name),
+                false => format!("+{}:refs/bundle/{}", name, name),
+            })
+            .collect();
+        if refspecs.is_empty() {
+            return Err(anyhow!("Bundle {} has no refs", self.path.display()));
+        }
+        
+        let mut fetch: Vec<&std::ffi::OsStr> = vec!["-C".as_ref(), dir.0.as_os_str(), "fetch".as_ref(), "--quiet".as_ref(), bundle.as_os_str()];
+        fetch.extend(refspecs.iter().map(std::ffi::OsStr::new));
+        run_git(&fetch)?;
+        
+        let repo_path = dir.0.to_str()
+            .ok_or_else(|| anyhow!("Invalid temporary directory"))?;
+        git::get_svcms_commits_with_validator(repo_path, &Revisions::new(Vec::new(), true), usize::MAX, validator)
+    }
+    
+    fn describe(&self) -> String {
+        format!("bundle {}", self.path.display())
+    }
+}
+
+/// A commit in an NDJSON export
+#[derive(Debug, Deserialize)]
+struct JsonCommit {
+    #[serde(alias = "hash", alias = "commit")]
+    sha: String,
+    #[serde(alias = "body")]
+    message: String,
+    #[serde(default, alias = "date")]
+    timestamp: Option<DateTime<FixedOffset>>,
+    #[serde(default)]
+    author: Option<String>,
+}
+
+/// Commits streamed as `git log -z --format=%H%x00%B` (with or without `-z`)
+/// or as NDJSON objects with `sha` and `message` (plus optional `timestamp`
+/// and `author`), newest first
+pub struct LogSource {
+    input: String,
+}
+
+impl LogSource {
+    pub fn new(input: impl Into<String>) -> Self {
+        LogSource { input: input.into() }
+    }
+    
+    pub fn from_reader(mut reader: impl Read) -> Result<Self> {
+        let mut input = String::new();
+        reader.read_to_string(&mut input)
+            .context("Failed to read commits from stdin")?;
+        Ok(Self::new(input))
+    }
+    
+    fn raw_commits(&self) -> Result<Vec<RawCommit>> {
+        if self.input.trim_start().starts_with('{') {
+            return self.input.lines()
+                .filter(|line| !line.trim().is_empty())
+                .enumerate()
+                .map(|(i, line)| {
+                    let commit: JsonCommit = serde_json::from_str(line)
+                        .with_context(|| format!("Invalid JSON commit on line {}", i + 1))?;
+                    Ok(RawCommit {
+                        sha: commit.sha,
+                        message: commit.message,
+                        timestamp: commit.timestamp,
+                        author: commit.author.map(|author| Person::parse(&author)),
+                        changed_files: None,
+                    })
+                })
+                .collect();
+        }
+        
+        Ok(parse_log_records(&self.input))
+    }
+}
+
+impl CommitSource for LogSource {
+    fn commits(&self, validator: Option<&CommitTypeValidator>) -> Result<Vec<SvcmsCommit>> {
+        parse_raw_commits(self.raw_commits()?, validator)
+    }
+    
+    fn describe(&self) -> String {
+        "commits from stdin".to_string()
+    }
+}
+
+/// Split `%H%x00%B` records
+///
+/// With `-z` records are NUL-separated too, so SHAs and messages alternate.
+/// Without it each message is followed by a newline and the next SHA.
+fn parse_log_records(input: &str) -> Vec<RawCommit> {
+    let mut tokens = input.split('\0');
+    let mut sha = tokens.next().map(|token| token.trim().to_string()).filter(|sha| !sha.is_empty());
+    let mut raw_commits = Vec::new();
+    
+    for token in tokens {
+        let token = token.trim_start_matches('\n');
+        let Some(current) = sha.take() else {
+            sha = Some(token.trim().to_string()).filter(|sha| !sha.is_empty());
+            continue;
+        };
+        
+        let trimmed = token.trim_end();
+        let (message, next) = match trimmed.rsplit_once('\n') {
+            Some((message, last)) if FULL_SHA_PATTERN.is_match(last.trim()) => (message, Some(last.trim().to_string())),
+            _ => (trimmed, None),
+        };
+        
+        raw_commits.push(RawCommit {
+            sha: current,
+            message: message.trim_end().to_string(),
+            ..Default::default()
+        });
+        sha = next;
+    }
+    
+    raw_commits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const SERIES: &str = "From 1111111111111111111111111111111111111111 Mon Sep 17 00:00:00 2001
+From: =?UTF-8?q?Ada=20L=C3=B6velace?= <ada@example.com>
+Date: Thu, 15 Oct 2026 14:30:00 +0200
+Subject: [PATCH 1/2] learned(api): rate limits reset at minute
+ boundaries
+
+Found while load testing.
+
+Memory: Rate limits reset at :00 of each minute
+---
+ src/api/client.rs | 3 ++-
+ 1 file changed, 2 insertions(+), 1 deletion(-)
+
+diff --git a/src/api/client.rs b/src/api/client.rs
+index 1234567..89abcde 100644
+--- a/src/api/client.rs
++++ b/src/api/client.rs
+@@ -1,2 +1,3 @@
+-let retry = 1;
++let retry = 2;
++let window = 60;
+ fn main() {}
+--
+2.44.0
+
+From 2222222222222222222222222222222222222222 Mon Sep 17 00:00:00 2001
+From: Grace Hopper <grace@example.com>
+Date: Fri, 16 Oct 2026 09:00:00 -0400
+Subject: [PATCH 2/2] Revert \"learned(api): rate limits reset at minute
+ boundaries\"
+
+This reverts commit 1111111111111111111111111111111111111111.
+---
+ src/api/retry.rs | 1 +
+ 1 file changed, 1 insertion(+)
+ create mode 100644 src/api/retry.rs
+
+diff --git a/src/api/retry.rs b/src/api/retry.rs
+new file mode 100644
+index 0000000..1234567
+--- /dev/null
++++ b/src/api/retry.rs
+@@ -0,0 +1 @@
++// Retry policy
+--
+2.44.0
+";
+
+    #[test]
+    fn test_parse_mbox() {
+        let raw = parse_mbox(SERIES);
+        assert_eq!(raw.len(), 2);
+        
+        assert_eq!(raw[0].sha, "1".repeat(40));
+        assert_eq!(raw[0].message, "learned(api): rate limits reset at minute boundaries\n\nFound while load testing.\n\nMemory: Rate limits reset at :00 of each minute");
+        assert_eq!(raw[0].author, Some(Person::new("Ada Lövelace", Some("ada@example.com".to_string()))));
+        assert_eq!(raw[0].timestamp.unwrap().to_rfc3339(), "2026-10-15T14:30:00+02:00");
+        assert_eq!(raw[0].changed_files, Some(vec![FileChange {
+            path: "src/api/client.rs".to_string(),
+            old_path: None,
+            status: ChangeStatus::Modified,
+            insertions: 2,
+            deletions: 1,
+        }]));
+        
+        let files = raw[1].changed_files.as_ref().unwrap();
+        assert_eq!(files[0].status, ChangeStatus::Added);
+        assert_eq!((files[0].insertions, files[0].deletions), (1, 0));
+    }
+    
+    #[test]
+    fn test_content_sha_is_the_blob_hash() {
+        // `echo hello | git hash-object --stdin`
+        assert_eq!(content_sha("hello\n"), "ce013625030ba8db");
+    }
+    
+    #[test]
+    fn test_mbox_source_links_reverts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("series.mbox");
+        fs::write(&path, SERIES).unwrap();
+        
+        let commits = open_source(path.to_str().unwrap()).unwrap().commits(None).unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].sha, "1111111");
+        assert_eq!(commits[0].reverted_by, Some("2222222".to_string()));
+        assert_eq!(commits[0].memory[0].content, "Rate limits reset at :00 of each minute");
+    }
+    
+    #[test]
+    fn test_parse_log_records() {
+        let (a, b) = ("a".repeat(40), "b".repeat(40));
+        
+        let with_z = format!("{}\0feat(api): add endpoint\n\nMemory: One\n\0{}\0fix(api): handle 429\n\0", a, b);
+        let without_z = format!("{}\0feat(api): add endpoint\n\nMemory: One\n\n{}\0fix(api): handle 429\n\n", a, b);
+        
+        for input in [with_z, without_z] {
+            let raw = parse_log_records(&input);
+            let records: Vec<(&str, &str)> = raw.iter().map(|r| (r.sha.as_str(), r.message.as_str())).collect();
+            assert_eq!(records, vec![
+                (a.as_str(), "feat(api): add endpoint\n\nMemory: One"),
+                (b.as_str(), "fix(api): handle 429"),
+            ]);
+        }
+    }
+    
+    #[test]
+    fn test_log_source_reads_ndjson() {
+        let input = r#"{"sha": "abc1234def", "message": "learned(api): x\n\nMemory: y", "timestamp": "2026-10-15T14:30:00+02:00", "author": "Ada <ada@example.com>"}
+{"hash": "def5678abc", "body": "Not an SVCMS commit"}
+"#;
+        let commits = LogSource::new(input).commits(None).unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].sha, "abc1234");
+        assert_eq!(commits[0].author_names(), vec!["Ada"]);
+        assert_eq!(commits[0].timestamp.to_rfc3339(), "2026-10-15T14:30:00+02:00");
+    }
+    
+    #[test]
+    fn test_bundle_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "learned(api): x\n\nMemory: From a bundle", &tree, &[]).unwrap();
+        
+        let bundle = dir.path().join("repo.bundle");
+        let status = Command::new("git")
+            .arg("-C").arg(dir.path())
+            .args(["bundle", "create", "--quiet"])
+            .arg(&bundle)
+            .arg("--all")
+            .status();
+        // Bundles need the git command
+        if !status.is_ok_and(|status| status.success()) {
+            return;
+        }
+        
+        let source = open_source(bundle.to_str().unwrap()).unwrap();
+        let commits = source.commits(None).unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].memory[0].content, "From a bundle");
+    }
+}
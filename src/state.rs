@@ -0,0 +1,237 @@
+//! Persisted sync state, so `synaptic sync` only processes new commits
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context, anyhow};
+use chrono::{DateTime, Utc};
+use git2::{Oid, Repository};
+use serde::{Deserialize, Serialize};
+use crate::git::Revisions;
+
+/// Commits walked without `--depth` by syncs that don't track state, like
+/// `--range` or `--all`
+pub const DEFAULT_DEPTH: usize = 100;
+
+/// Sync state of a repository, stored in `.synaptic/state.toml`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SyncState {
+    /// Branch name (or `HEAD` when detached) → last synced commit
+    #[serde(default)]
+    pub branches: BTreeMap<String, BranchState>,
+    /// Hidden IDs of memories that `synaptic cleanup` took out of CLAUDE.md,
+    /// so later syncs don't write them back
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub retired: BTreeSet<String>,
+    /// SHAs of commits synced `--from` an export, as they appear in hidden
+    /// IDs; they may never be in this repository
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub imported: BTreeSet<String>,
+}
+
+/// Where sync stopped on one branch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BranchState {
+    /// Full OID of the last synced commit
+    pub last_synced: String,
+    pub synced_at: DateTime<Utc>,
+}
+
+/// What an incremental sync should walk
+#[derive(Debug, PartialEq)]
+pub enum SyncCursor {
+    /// Nothing synced on this branch yet, or `--full`: walk the whole branch
+    Fresh,
+    /// Walk only the commits after the last synced one
+    Resume(Revisions),
+    /// The last synced commit is no longer in the branch history (force-push
+    /// or rebase): walk the whole branch again
+    Reset { last_synced: String },
+}
+
+impl SyncCursor {
+    /// Commits to walk from HEAD: everything new when resuming, otherwise
+    /// `requested` or the whole branch, so memories older than any window
+    /// that were never synced are reached
+    pub fn depth(&self, requested: Option<usize>) -> usize {
+        match self {
+            SyncCursor::Resume(_) => usize::MAX,
+            SyncCursor::Fresh | SyncCursor::Reset { .. } => requested.unwrap_or(usize::MAX),
+        }
+    }
+}
+
+/// HEAD of the repository, as recorded in the sync state
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncHead {
+    /// Branch name, or `HEAD` when detached
+    pub branch: String,
+    pub oid: String,
+}
+
+impl SyncState {
+    /// Path of the state file for the repository containing `repo_path`
+    pub fn path(repo_path: &Path) -> Result<PathBuf> {
+        let repo = Repository::discover(repo_path)
+            .context("Not in a git repository")?;
+        let root = repo.workdir()
+            .ok_or_else(|| anyhow!("Repository has no working directory"))?;
+        Ok(root.join(".synaptic").join("state.toml"))
+    }
+    
+    /// Load the state for a repository (empty when never synced)
+    pub fn load(repo_path: &Path) -> Result<Self> {
+        let path = Self::path(repo_path)?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        
+        let content = fs::read_to_string(&path)
+            .context("Failed to read sync state")?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse sync state at {}", path.display()))
+    }
+    
+    /// Save the state for a repository
+    pub fn save(&self, repo_path: &Path) -> Result<()> {
+        let path = Self::path(repo_path)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        
+        let content = toml::to_string_pretty(self)
+            .context("Failed to serialize sync state")?;
+        fs::write(&path, content)
+            .context("Failed to write sync state")
+    }
+    
+    /// Record that a branch has been synced up to `head`
+    pub fn record(&mut self, head: &SyncHead) {
+        self.branches.insert(head.branch.clone(), BranchState {
+            last_synced: head.oid.clone(),
+            synced_at: Utc::now(),
+        });
+    }
+    
+    /// Decide what to walk to sync `head` incrementally
+    pub fn cursor(&self, repo_path: &Path, head: &SyncHead) -> Result<SyncCursor> {
+        let Some(branch) = self.branches.get(&head.branch) else {
+            return Ok(SyncCursor::Fresh);
+        };
+        
+        let repo = Repository::discover(repo_path)
+            .context("Not in a git repository")?;
+        let head_oid = Oid::from_str(&head.oid)?;
+        
+        // The cursor must still be HEAD or one of its ancestors
+        let in_history = Oid::from_str(&branch.last_synced).ok()
+            .filter(|oid| repo.find_commit(*oid).is_ok())
+            .is_some_and(|oid| oid == head_oid || repo.graph_descendant_of(head_oid, oid).unwrap_or(false));
+        
+        if in_history {
+            Ok(SyncCursor::Resume(Revisions::new(
+                vec![head.oid.clone(), format!("^{}", branch.last_synced)],
+                false,
+            )))
+        } else {
+            Ok(SyncCursor::Reset { last_synced: branch.last_synced.clone() })
+        }
+    }
+}
+
+/// Current branch and HEAD commit of a repository
+pub fn current_head(repo_path: &Path) -> Result<SyncHead> {
+    let repo = Repository::discover(repo_path)
+        .context("Not in a git repository")?;
+    let head = repo.head()
+        .context("Repository has no HEAD commit")?;
+    
+    let branch = if head.is_branch() {
+        head.shorthand().unwrap_or("HEAD").to_string()
+    } else {
+        "HEAD".to_string()
+    };
+    let oid = head.peel_to_commit()?.id().to_string();
+    
+    Ok(SyncHead { branch, oid })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    
+    fn commit(repo: &Repository, message: &str, parent: Option<Oid>) -> Oid {
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap()).unwrap();
+        let parent = parent.map(|p| repo.find_commit(p).unwrap());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(None, &sig, &sig, message, &tree, &parents).unwrap()
+    }
+    
+    fn checkout(repo: &Repository, oid: Oid) {
+        repo.reference("refs/heads/main", oid, true, "test").unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+    }
+    
+    #[test]
+    fn test_cursor_follows_branch_history() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        
+        let first = commit(&repo, "feat(core): first", None);
+        checkout(&repo, first);
+        
+        let mut state = SyncState::load(dir.path()).unwrap();
+        let head = current_head(dir.path()).unwrap();
+        assert_eq!(head.branch, "main");
+        assert_eq!(state.cursor(dir.path(), &head).unwrap(), SyncCursor::Fresh);
+        
+        state.record(&head);
+        state.save(dir.path()).unwrap();
+        let state = SyncState::load(dir.path()).unwrap();
+        assert_eq!(state.branches["main"].last_synced, first.to_string());
+        
+        // New commits on top resume from the cursor
+        let second = commit(&repo, "feat(core): second", Some(first));
+        checkout(&repo, second);
+        let head = current_head(dir.path()).unwrap();
+        assert_eq!(
+            state.cursor(dir.path(), &head).unwrap(),
+            SyncCursor::Resume(Revisions::new(vec![second.to_string(), format!("^{}", first)], false))
+        );
+        
+        // A rewritten branch no longer contains the cursor
+        let rewritten = commit(&repo, "feat(core): rewritten", None);
+        checkout(&repo, rewritten);
+        let head = current_head(dir.path()).unwrap();
+        assert_eq!(
+            state.cursor(dir.path(), &head).unwrap(),
+            SyncCursor::Reset { last_synced: first.to_string() }
+        );
+    }
+    
+    #[test]
+    fn test_fresh_sync_reaches_memories_older_than_default_depth() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        
+        let mut parent = commit(&repo, "learned(core): old lesson\n\nMemory: Never synced before", None);
+        for n in 0..DEFAULT_DEPTH {
+            parent = commit(&repo, &format!("chore(core): change {}", n), Some(parent));
+        }
+        checkout(&repo, parent);
+        
+        let head = current_head(dir.path()).unwrap();
+        let cursor = SyncState::default().cursor(dir.path(), &head).unwrap();
+        assert_eq!(cursor, SyncCursor::Fresh);
+        
+        let path = dir.path().to_str().unwrap();
+        let commits = crate::git::get_svcms_commits_with_validator(path, &Revisions::head(), cursor.depth(None), None).unwrap();
+        assert!(commits.iter().any(|c| c.memory.iter().any(|m| m.content == "Never synced before")));
+        
+        // An explicit --depth still bounds the walk
+        let commits = crate::git::get_svcms_commits_with_validator(path, &Revisions::head(), cursor.depth(Some(DEFAULT_DEPTH)), None).unwrap();
+        assert!(commits.iter().all(|c| c.memory.is_empty()));
+    }
+}
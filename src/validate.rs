@@ -0,0 +1,540 @@
+//! Finding synced memories that no longer describe the code
+//!
+//! A memory goes stale when what it points at disappears (its `Location:`,
+//! a referenced file, its scope's directory), when its commit is rebased
+//! away, or when it is old and the files it was learned on have changed a lot.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+use anyhow::{Result, Context};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use git2::{Oid, Repository, Sort};
+use serde::Serialize;
+use crate::SvcmsCommit;
+use crate::cleanup::find_claude_md_files;
+use crate::config::SynapticConfig;
+use crate::locations::{infer_scope_location, Placement, ScopeGroups};
+use crate::memory::{entry_id, entry_is_retracted, entry_sha, find_memories_section, memory_entries, read_claude_md};
+use crate::parser::{parse_commit_message, split_squash_message};
+use crate::state::SyncState;
+
+/// Age after which a memory is checked for churn, when none is configured
+pub const DEFAULT_STALE_AFTER_DAYS: u32 = 180;
+/// Later commits to a memory's files that make an old memory stale, when none is configured
+pub const DEFAULT_CHURN_THRESHOLD: usize = 10;
+
+/// What counts as stale
+#[derive(Debug, Clone)]
+pub struct ValidationOptions {
+    /// Memories older than this many days are checked for churn
+    pub stale_after_days: u32,
+    /// Later commits touching the memory's files that make it stale
+    pub churn_threshold: usize,
+    /// Scope routing, to find each scope's directory
+    pub scope_groups: ScopeGroups,
+}
+
+impl Default for ValidationOptions {
+    fn default() -> Self {
+        ValidationOptions {
+            stale_after_days: DEFAULT_STALE_AFTER_DAYS,
+            churn_threshold: DEFAULT_CHURN_THRESHOLD,
+            scope_groups: ScopeGroups::default(),
+        }
+    }
+}
+
+impl ValidationOptions {
+    /// Take the thresholds from `[cleanup.validation]` and the scope groups from `[commit_types.scopes]`
+    pub fn from_config(config: &SynapticConfig) -> Self {
+        let validation = config.validation();
+        ValidationOptions {
+            stale_after_days: validation
+                .and_then(|v| v.stale_after_days)
+                .unwrap_or(DEFAULT_STALE_AFTER_DAYS),
+            churn_threshold: validation
+                .and_then(|v| v.churn_threshold)
+                .unwrap_or(DEFAULT_CHURN_THRESHOLD),
+            scope_groups: config.scopes()
+                .map(ScopeGroups::from_config)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Why a memory is stale
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StaleIssue {
+    /// The CLAUDE.md named by the memory's `Location:` is gone
+    MissingLocation { location: String },
+    /// A file the memory links to is gone
+    MissingReference { target: String },
+    /// The scope's directory existed at the commit and is gone now
+    ScopeDirectoryDeleted { scope: String, directory: String },
+    /// The repository doesn't have the commit
+    CommitNotFound,
+    /// The commit isn't in HEAD's history, e.g. after a rebase
+    UnreachableCommit,
+    /// The memory is old and its files changed in many commits since
+    Churned { age_days: i64, commits: usize },
+}
+
+impl std::fmt::Display for StaleIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StaleIssue::MissingLocation { location } => write!(f, "Location {} no longer exists", location),
+            StaleIssue::MissingReference { target } => write!(f, "referenced file {} no longer exists", target),
+            StaleIssue::ScopeDirectoryDeleted { scope, directory } => {
+                write!(f, "directory {} of scope {} was deleted", directory, scope)
+            }
+            StaleIssue::CommitNotFound => write!(f, "commit not found in the repository"),
+            StaleIssue::UnreachableCommit => write!(f, "commit is no longer reachable from HEAD"),
+            StaleIssue::Churned { age_days, commits } => {
+                write!(f, "{} days old, its files changed in {} commits since", age_days, commits)
+            }
+        }
+    }
+}
+
+/// A synced memory with at least one issue
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleMemory {
+    /// CLAUDE.md it is in, relative to the project root
+    pub path: PathBuf,
+    /// SHA the entry was synced from
+    pub sha: String,
+    /// Hidden ID, for entries synced with one
+    pub id: Option<String>,
+    /// First line of the entry
+    pub memory: String,
+    pub issues: Vec<StaleIssue>,
+}
+
+/// Result of validating a project's memories
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ValidationReport {
+    /// Synced memories checked
+    pub checked: usize,
+    /// Memories synced `--from` an export whose commit isn't in the
+    /// repository, so only their links could be checked
+    pub imported: usize,
+    pub stale: Vec<StaleMemory>,
+}
+
+/// A synced entry found in a CLAUDE.md
+struct SyncedEntry {
+    path: PathBuf,
+    sha: String,
+    id: Option<String>,
+    index: Option<usize>,
+    lines: Vec<String>,
+}
+
+/// Check every synced memory in the project's CLAUDE.md files
+///
+/// Struck-through (reverted) and hand-written entries are left out.
+pub fn validate_project(project_root: &Path, options: &ValidationOptions) -> Result<ValidationReport> {
+    let repo = Repository::discover(project_root)
+        .context("Failed to open Git repository")?;
+    let imported = SyncState::load(project_root)?.imported;
+    validate_entries(project_root, &repo, options, &imported, Utc::now())
+}
+
+fn validate_entries(
+    project_root: &Path,
+    repo: &Repository,
+    options: &ValidationOptions,
+    imported: &BTreeSet<String>,
+    now: DateTime<Utc>
+) -> Result<ValidationReport> {
+    let entries = synced_entries(project_root, repo)?;
+    let head = repo.head().ok().and_then(|head| head.target());
+    let stale_before = now.timestamp() - i64::from(options.stale_after_days) * 86400;
+    let mut checked: Vec<(SyncedEntry, Vec<StaleIssue>)> = Vec::new();
+    // Old memories, by position in `checked`: their commit time and files
+    let mut old: Vec<(usize, i64, Vec<String>)> = Vec::new();
+    let mut imported_count = 0;
+
+    for entry in entries {
+        let dir = entry.path.parent().unwrap_or(project_root);
+        let mut issues: Vec<StaleIssue> = markdown_links(&entry.lines)
+            .filter(|target| !normalize(&dir.join(target)).exists())
+            .map(|target| StaleIssue::MissingReference { target: target.to_string() })
+            .collect();
+
+        match repo.revparse_single(&entry.sha).and_then(|object| object.peel_to_commit()) {
+            Err(_) if entry.is_imported(imported) => imported_count += 1,
+            Err(_) => issues.push(StaleIssue::CommitNotFound),
+            Ok(commit) => {
+                let reachable = head.is_some_and(|head| {
+                    head == commit.id() || repo.graph_descendant_of(head, commit.id()).unwrap_or(false)
+                });
+                if !reachable {
+                    issues.push(StaleIssue::UnreachableCommit);
+                }
+
+                if let Some(svcms_commit) = source_commit(&commit, entry.index) {
+                    issues.extend(location_issues(&svcms_commit, project_root, repo, &commit, options));
+                }
+
+                let time = commit.time().seconds();
+                if time < stale_before {
+                    old.push((checked.len(), time, touched_paths(repo, &commit)?));
+                }
+            }
+        }
+
+        checked.push((entry, issues));
+    }
+
+    if let Some(oldest) = old.iter().map(|(_, time, _)| *time).min() {
+        let history = FileHistory::walk(repo, oldest)?;
+        for (i, time, paths) in old {
+            let commits = history.changes_since(&paths, time);
+            if commits >= options.churn_threshold {
+                checked[i].1.push(StaleIssue::Churned { age_days: (now.timestamp() - time) / 86400, commits });
+            }
+        }
+    }
+
+    let mut report = ValidationReport { checked: checked.len(), imported: imported_count, stale: Vec::new() };
+    for (entry, issues) in checked {
+        if issues.is_empty() {
+            continue;
+        }
+        report.stale.push(StaleMemory {
+            path: entry.path.strip_prefix(project_root).unwrap_or(&entry.path).to_path_buf(),
+            memory: entry.lines.first()
+                .map(|line| summarize(line))
+                .unwrap_or_default(),
+            sha: entry.sha,
+            id: entry.id,
+            issues,
+        });
+    }
+
+    Ok(report)
+}
+
+impl SyncedEntry {
+    /// Whether the entry came from an export rather than this repository
+    ///
+    /// Either sync recorded its SHA, or its hidden ID has a short SHA: syncs
+    /// from the repository always write the full one.
+    fn is_imported(&self, imported: &BTreeSet<String>) -> bool {
+        let recorded = imported.iter().any(|sha| sha.starts_with(&self.sha) || self.sha.starts_with(sha.as_str()));
+        recorded || (self.id.is_some() && self.sha.len() < 40)
+    }
+}
+
+/// Synced, unretracted entries of every CLAUDE.md in the project
+fn synced_entries(project_root: &Path, repo: &Repository) -> Result<Vec<SyncedEntry>> {
+    let mut synced = Vec::new();
+
+    for path in find_claude_md_files(project_root, Some(repo))? {
+        let content = read_claude_md(&path)?;
+        let Some((start, end)) = find_memories_section(&content) else {
+            continue;
+        };
+        for entry in memory_entries(&content[start..end]) {
+            let Some(sha) = entry_sha(&entry).filter(|_| !entry_is_retracted(&entry)) else {
+                continue;
+            };
+            let id = entry_id(&entry);
+            synced.push(SyncedEntry {
+                path: path.clone(),
+                sha: sha.to_string(),
+                id: id.map(|(sha, index)| format!("{}:{}", sha, index)),
+                index: id.and_then(|(_, index)| index.parse().ok()),
+                lines: entry.iter().map(|line| line.to_string()).collect(),
+            });
+        }
+    }
+
+    Ok(synced)
+}
+
+/// Relative link targets in an entry, without anchors
+fn markdown_links(lines: &[String]) -> impl Iterator<Item = &str> {
+    lines.iter().flat_map(|line| {
+        line.match_indices("](").filter_map(|(i, _)| {
+            let rest = &line[i + 2..];
+            let target = &rest[..rest.find(')')?];
+            let target = target.split('#').next().unwrap_or_default();
+            (!target.is_empty() && !target.contains("://") && !target.starts_with('<')).then_some(target)
+        })
+    })
+}
+
+/// Resolve `.` and `..` without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// The SVCMS commit a memory came from, read from its git commit's message
+///
+/// The hidden ID's index picks the memory; squash pieces are numbered across
+/// the whole commit, as sync numbers them.
+fn source_commit(commit: &git2::Commit, index: Option<usize>) -> Option<SvcmsCommit> {
+    let message = commit.message()?;
+    let timestamp = DateTime::from_timestamp(commit.time().seconds(), 0)?.fixed_offset();
+    let parse = |message: &str| parse_commit_message(&commit.id().to_string(), message, timestamp).ok().flatten();
+
+    let pieces: Vec<SvcmsCommit> = match parse(message) {
+        Some(parsed) if !parsed.memory.is_empty() => vec![parsed],
+        parsed => split_squash_message(message)
+            .map(|messages| messages.iter().filter_map(|message| parse(message)).collect())
+            .filter(|pieces: &Vec<SvcmsCommit>| !pieces.is_empty())
+            .or_else(|| parsed.map(|parsed| vec![parsed]))?,
+    };
+
+    let mut index = index.unwrap_or(0);
+    for mut piece in pieces {
+        if index < piece.memory.len() {
+            let entry = piece.memory.swap_remove(index);
+            piece.location = entry.location.or(piece.location);
+            return Some(piece);
+        }
+        index -= piece.memory.len();
+    }
+    None
+}
+
+/// Issues with where a memory was placed: its `Location:` and its scope's directory
+fn location_issues(
+    svcms_commit: &SvcmsCommit,
+    project_root: &Path,
+    repo: &Repository,
+    commit: &git2::Commit,
+    options: &ValidationOptions
+) -> Vec<StaleIssue> {
+    let mut issues = Vec::new();
+
+    if let Some(location) = &svcms_commit.location {
+        if !project_root.join(location).exists() {
+            issues.push(StaleIssue::MissingLocation { location: location.clone() });
+        }
+    }
+
+    if let Some(scope) = &svcms_commit.scope {
+        let target = match options.scope_groups.locate(scope) {
+            Some((target, Placement::ScopeGroup("modules"))) => Some(target),
+            Some(_) => None,
+            None => Some(infer_scope_location(scope)),
+        };
+        let directory = target.as_deref().and_then(Path::parent).filter(|dir| !dir.as_os_str().is_empty());
+        if let Some(directory) = directory {
+            let workdir = repo.workdir().unwrap_or(project_root);
+            let in_repo = project_root.join(directory);
+            let existed = in_repo.strip_prefix(workdir).ok()
+                .and_then(|relative| commit.tree().ok()?.get_path(relative).ok())
+                .is_some();
+            if existed && !in_repo.exists() {
+                issues.push(StaleIssue::ScopeDirectoryDeleted {
+                    scope: scope.clone(),
+                    directory: directory.display().to_string(),
+                });
+            }
+        }
+    }
+
+    issues
+}
+
+/// Paths a commit changed relative to its first parent
+fn touched_paths(repo: &Repository, commit: &git2::Commit) -> Result<Vec<String>> {
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+
+    Ok(diff.deltas()
+        .filter_map(|delta| delta.new_file().path().or(delta.old_file().path()))
+        .map(|path| path.to_string_lossy().to_string())
+        .collect())
+}
+
+/// When each file changed, from HEAD back to a cutoff
+struct FileHistory {
+    changes: HashMap<String, Vec<(i64, Oid)>>,
+}
+
+impl FileHistory {
+    /// Walk HEAD's history newest first, down to the first commit before `since`
+    ///
+    /// Merge commits are skipped; their changes are counted on the branch.
+    fn walk(repo: &Repository, since: i64) -> Result<Self> {
+        let mut changes: HashMap<String, Vec<(i64, Oid)>> = HashMap::new();
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(Sort::TIME)?;
+        revwalk.push_head()?;
+
+        for oid in revwalk {
+            let commit = repo.find_commit(oid?)?;
+            let time = commit.time().seconds();
+            if time < since {
+                break;
+            }
+            if commit.parent_count() > 1 {
+                continue;
+            }
+            for path in touched_paths(repo, &commit)? {
+                changes.entry(path).or_default().push((time, commit.id()));
+            }
+        }
+
+        Ok(FileHistory { changes })
+    }
+
+    /// Commits after `since` that touched any of the paths
+    fn changes_since(&self, paths: &[String], since: i64) -> usize {
+        let mut commits: Vec<Oid> = paths.iter()
+            .filter_map(|path| self.changes.get(path))
+            .flatten()
+            .filter(|(time, _)| *time > since)
+            .map(|(_, oid)| *oid)
+            .collect();
+        commits.sort();
+        commits.dedup();
+        commits.len()
+    }
+}
+
+/// First line of an entry without its bullet and hidden ID, shortened for display
+fn summarize(line: &str) -> String {
+    let line = line.trim_start_matches("- ");
+    let line = line.split(" <!-- synaptic:").next().unwrap_or(line);
+    match line.char_indices().nth(80) {
+        Some((i, _)) => format!("{}…", &line[..i]),
+        None => line.to_string(),
+    }
+}
+
+/// Print a report for people
+pub fn print_report(report: &ValidationReport) {
+    for memory in &report.stale {
+        println!("{} {} ({})", "⚠️".yellow(), memory.memory, memory.path.display());
+        for issue in &memory.issues {
+            println!("   - {}", issue);
+        }
+    }
+
+    if report.stale.is_empty() {
+        println!("✅ {} synced memories checked, none stale", report.checked);
+    } else {
+        println!("\n🔍 {} of {} synced memories are stale", report.stale.len(), report.checked);
+    }
+    if report.imported > 0 {
+        println!("   {} imported memories have no commit in this repository; only their links were checked", report.imported);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use git2::{Signature, Time};
+
+    /// Commit the working tree's files at a time, on HEAD or detached
+    fn commit_at(repo: &Repository, seconds: i64, message: &str, update_head: bool) -> Oid {
+        let mut index = repo.index().unwrap();
+        index.add_all(["src"], git2::IndexAddOption::DEFAULT, None).unwrap();
+        index.update_all(["src"], None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::new("Ada", "ada@acme.com", &Time::new(seconds, 0)).unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(update_head.then_some("HEAD"), &sig, &sig, message, &tree, &parents).unwrap()
+    }
+
+    #[test]
+    fn test_validate_reports_stale_memories() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let repo = Repository::init(root).unwrap();
+        let now = DateTime::parse_from_rfc3339("2026-10-16T12:00:00Z").unwrap().with_timezone(&Utc);
+        let day = 86400;
+
+        fs::create_dir_all(root.join("src/auth")).unwrap();
+        fs::create_dir_all(root.join("src/api")).unwrap();
+        fs::write(root.join("src/auth/login.rs"), "v0").unwrap();
+        fs::write(root.join("src/api/client.rs"), "v0").unwrap();
+        let auth = commit_at(&repo, now.timestamp() - 300 * day,
+            "feat(auth): add login\n\nMemory: Tokens expire after 24h\nLocation: src/session/CLAUDE.md", true);
+        for i in 1..=3 {
+            fs::write(root.join("src/auth/login.rs"), format!("v{}", i)).unwrap();
+            commit_at(&repo, now.timestamp() - (100 - i) * day, &format!("chore(auth): tweak {}", i), true);
+        }
+        let api = commit_at(&repo, now.timestamp() - day, "fix(api): retry\n\nMemory: Retry on 429", true);
+        fs::write(root.join("src/api/client.rs"), "rebased away").unwrap();
+        let detached = commit_at(&repo, now.timestamp(), "fix(api): backoff\n\nMemory: Back off", false);
+        fs::remove_dir_all(root.join("src/auth")).unwrap();
+        fs::write(root.join("src/api/client.rs"), "v0").unwrap();
+
+        fs::write(root.join("CLAUDE.md"), format!(
+            "# CLAUDE.md\n\n<!-- synaptic:begin -->\n## SVCMS Memories\n\n\
+- Back off: fix `fix(api): backoff` ({}) <!-- synaptic:{}:0 -->\n\
+- Retry on 429: fix `fix(api): retry` ({}) — refs: [src/api/client.rs](src/api/client.rs), [docs/retry.md](docs/retry.md#L3) <!-- synaptic:{}:0 -->\n\
+- Tokens expire after 24h: feat `feat(auth): add login` ({}) <!-- synaptic:{}:0 -->\n\
+- Lost: learned `learned: lost` (abcdef1) <!-- synaptic:abcdef1234567abcdef1234567abcdef12345678:0 -->\n\
+- Patch: learned `learned: patch` (c0ffee1) <!-- synaptic:c0ffee1:0 -->\n\
+- ~~Gone: learned `learned: gone` (bcdef12)~~ *(reverted in 9999999)* <!-- synaptic:bcdef12:0 -->\n\
+- Hand-written note\n<!-- synaptic:end -->\n",
+            &detached.to_string()[..7], detached, &api.to_string()[..7], api, &auth.to_string()[..7], auth
+        )).unwrap();
+
+        let options = ValidationOptions { churn_threshold: 3, ..Default::default() };
+        let report = validate_entries(root, &repo, &options, &BTreeSet::new(), now).unwrap();
+        assert_eq!(report.checked, 5);
+        assert_eq!(report.stale.len(), 4);
+        // The short-SHA ID came from an export, so its missing commit isn't an issue
+        assert_eq!(report.imported, 1);
+
+        assert_eq!(report.stale[0].issues, vec![StaleIssue::UnreachableCommit]);
+        assert!(report.stale[1].memory.starts_with("Retry on 429: fix `fix(api): retry`"));
+        assert!(report.stale[1].memory.ends_with('…'));
+        assert_eq!(report.stale[1].issues, vec![StaleIssue::MissingReference { target: "docs/retry.md".to_string() }]);
+        assert_eq!(report.stale[2].id, Some(format!("{}:0", auth)));
+        assert_eq!(report.stale[2].issues, vec![
+            StaleIssue::MissingLocation { location: "src/session/CLAUDE.md".to_string() },
+            StaleIssue::ScopeDirectoryDeleted { scope: "auth".to_string(), directory: "src/auth".to_string() },
+            StaleIssue::Churned { age_days: 300, commits: 3 },
+        ]);
+        assert_eq!(report.stale[3].issues, vec![StaleIssue::CommitNotFound]);
+
+        // Below the churn threshold, an old memory is fine
+        let options = ValidationOptions { churn_threshold: 4, ..options };
+        let report = validate_entries(root, &repo, &options, &BTreeSet::new(), now).unwrap();
+        assert_eq!(report.stale[2].issues.len(), 2);
+
+        // Exports recorded by sync are skipped even with a full SHA
+        let imported: BTreeSet<String> = ["abcdef1234567".to_string()].into();
+        let report = validate_entries(root, &repo, &options, &imported, now).unwrap();
+        assert_eq!(report.imported, 2);
+        assert_eq!(report.stale.len(), 3);
+    }
+
+    #[test]
+    fn test_markdown_links() {
+        let lines = vec![
+            "- Memory — refs: [a.rs](../src/a.rs#L4), [#87](https://github.com/acme/app/issues/87), <https://example.com>".to_string(),
+            "  see [notes](./docs/notes.md)".to_string(),
+        ];
+        assert_eq!(markdown_links(&lines).collect::<Vec<_>>(), vec!["../src/a.rs", "./docs/notes.md"]);
+        assert_eq!(normalize(Path::new("/repo/src/auth/../api/./client.rs")), PathBuf::from("/repo/src/api/client.rs"));
+    }
+}
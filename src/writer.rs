@@ -0,0 +1,231 @@
+//! Render SVCMS commits back to canonical commit messages
+
+use crate::SvcmsCommit;
+
+/// Footers that fold onto one line are wrapped at this width
+const WRAP_WIDTH: usize = 72;
+
+/// Indentation of trailer continuation lines
+const CONTINUATION_INDENT: &str = "  ";
+
+impl SvcmsCommit {
+    /// Render this commit as a canonical SVCMS commit message
+    pub fn to_message(&self) -> String {
+        render_commit_message(self)
+    }
+}
+
+/// Render a commit as a canonical SVCMS commit message
+///
+/// The header uses the two-tier `category.type(scope)` form whenever the
+/// category is known, and marks breaking changes with `!`. The body is kept
+/// verbatim. Footers come in a fixed order: `BREAKING CHANGE`, `Context`,
+/// `Refs`, `Location`, each `Memory` with its paired `Location`, `Tags`, all
+/// other footers in their original order, then `Co-authored-by`. `Context`,
+/// `Refs` and `Tags` are wrapped at 72 columns; memories keep their own line
+/// breaks. A cherry-pick line closes the message, as `git cherry-pick -x`
+/// writes it.
+///
+/// For any commit produced by the parser, parsing the rendered message with
+/// the same validator, SHA and timestamp yields the same commit.
+pub fn render_commit_message(commit: &SvcmsCommit) -> String {
+    let mut message = render_header(commit);
+
+    if let Some(body) = &commit.body {
+        message.push_str("\n\n");
+        message.push_str(body);
+    }
+
+    let footers = render_footers(commit);
+    if !footers.is_empty() {
+        message.push_str("\n\n");
+        message.push_str(&footers.join("\n"));
+    }
+    
+    if let Some(sha) = &commit.cherry_picked_from {
+        message.push_str(if footers.is_empty() { "\n\n" } else { "\n" });
+        message.push_str(&format!("(cherry picked from commit {})", sha));
+    }
+
+    message
+}
+
+/// Render the `[category.]type(scope)[!]: summary` header
+fn render_header(commit: &SvcmsCommit) -> String {
+    let mut header = String::new();
+
+    if let Some(category) = &commit.category {
+        header.push_str(category);
+        header.push('.');
+    }
+    header.push_str(&commit.commit_type);
+
+    if let Some(scope) = &commit.scope {
+        header.push_str(&format!("({})", scope));
+    }
+    if commit.breaking {
+        header.push('!');
+    }
+
+    header.push_str(": ");
+    header.push_str(&commit.summary);
+    header
+}
+
+/// Render the footer lines in canonical order
+fn render_footers(commit: &SvcmsCommit) -> Vec<String> {
+    let mut footers = Vec::new();
+
+    if let Some(breaking_change) = &commit.breaking_change {
+        footers.push(multiline_footer("BREAKING CHANGE", breaking_change));
+    }
+    if let Some(context) = &commit.context {
+        footers.push(wrapped_footer("Context", context));
+    }
+    if !commit.refs.is_empty() {
+        let refs: Vec<String> = commit.refs.iter().map(|r| r.to_string()).collect();
+        footers.push(wrapped_footer("Refs", &refs.join(", ")));
+    }
+
+    // The parser takes the first Location as the commit-level one, so it only
+    // needs its own line when the first paired Location doesn't already say it
+    let first_paired_location = commit.memory.iter().find_map(|entry| entry.location.as_ref());
+    if let Some(location) = &commit.location {
+        if first_paired_location != Some(location) {
+            footers.push(format!("Location: {}", location));
+        }
+    }
+
+    for entry in &commit.memory {
+        footers.push(multiline_footer("Memory", &entry.content));
+        if let Some(location) = &entry.location {
+            footers.push(format!("Location: {}", location));
+        }
+    }
+
+    if !commit.tags.is_empty() {
+        footers.push(wrapped_footer("Tags", &commit.tags.join(", ")));
+    }
+
+    for (token, values) in &commit.footers {
+        for value in values {
+            footers.push(multiline_footer(token, value));
+        }
+    }
+
+    for co_author in &commit.co_authors {
+        footers.push(format!("Co-authored-by: {}", co_author));
+    }
+
+    footers
+}
+
+/// Render a footer keeping the value's line breaks as continuation lines
+fn multiline_footer(token: &str, value: &str) -> String {
+    let mut lines = value.lines();
+    let mut footer = format!("{}: {}", token, lines.next().unwrap_or_default());
+
+    for line in lines {
+        footer.push('\n');
+        footer.push_str(CONTINUATION_INDENT);
+        footer.push_str(line);
+    }
+
+    footer
+}
+
+/// Render a single-line footer, wrapping long values onto continuation lines
+fn wrapped_footer(token: &str, value: &str) -> String {
+    let mut footer = format!("{}:", token);
+    let mut line_length = footer.len();
+
+    for (i, word) in value.split_whitespace().enumerate() {
+        let word_length = word.chars().count();
+        if i > 0 && line_length + 1 + word_length > WRAP_WIDTH {
+            footer.push('\n');
+            footer.push_str(CONTINUATION_INDENT);
+            line_length = CONTINUATION_INDENT.len();
+        } else {
+            footer.push(' ');
+            line_length += 1;
+        }
+        footer.push_str(word);
+        line_length += word_length;
+    }
+
+    footer
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse_commit_message;
+    use chrono::Utc;
+
+    fn assert_roundtrip(message: &str) {
+        let timestamp = Utc::now();
+        let commit = parse_commit_message("abc1234", message, timestamp).unwrap().unwrap();
+        let rendered = commit.to_message();
+        let reparsed = parse_commit_message("abc1234", &rendered, timestamp).unwrap().unwrap();
+
+        assert_eq!(reparsed, commit, "rendered message:\n{}", rendered);
+        // Canonical messages render to themselves
+        assert_eq!(reparsed.to_message(), rendered);
+    }
+
+    #[test]
+    fn test_roundtrip_invariant() {
+        assert_roundtrip("feat(api): add endpoint");
+        assert_roundtrip("fixed: legacy type without category");
+        assert_roundtrip("refactor(auth)!: rename helpers\n\nBREAKING CHANGE: `get_session` moved\n  to `auth::session`");
+        assert_roundtrip(r#"learned(api): rate limiting resets at minute boundaries
+
+Discovered through testing that the API rate limiter uses fixed minute
+boundaries rather than a rolling 60-second window.
+
+Second paragraph of the body.
+
+Context: Staff Scheduling API integration, which has a long description that needs wrapping
+Refs: #87, src/api/client.ts#L12, a1b2c3d, https://example.com, design notes
+Memory: API rate limit resets at :00 seconds of each minute
+Location: src/api/CLAUDE.md
+Memory: Build clients with explicit timeouts:
+    Client::builder()
+        .timeout(Duration::from_secs(5))
+Location: src/http/CLAUDE.md
+Tags: api, rate-limiting, retry-strategy
+Co-authored-by: Ada <ada@example.com>
+Fixes #12
+Co-authored-by: Grace <grace@example.com>"#);
+        assert_roundtrip("fix(api): retry on 429\n\n(cherry picked from commit 0123456789abcdef0123456789abcdef01234567)");
+        assert_roundtrip("fix(api): retry on 429\n\nMemory: Retry after the reset\n(cherry picked from commit 0123456)");
+        assert_roundtrip("decision(db): use postgres\n\nLocation: ./CLAUDE.md\nMemory: No location of its own\nMemory: Paired\nLocation: db/CLAUDE.md");
+    }
+
+    #[test]
+    fn test_render_canonical_form() {
+        let message = "Tags: b, a\nMemory: insight\nContext: ctx";
+        let commit = parse_commit_message("abc1234", &format!("learned(api)!: summary\n\n{}", message), Utc::now())
+            .unwrap().unwrap();
+
+        assert_eq!(
+            commit.to_message(),
+            "knowledge.learned(api)!: summary\n\nContext: ctx\nMemory: insight\nTags: b, a"
+        );
+    }
+
+    #[test]
+    fn test_wrapped_footer() {
+        let footer = super::wrapped_footer("Tags", &["keyword"; 12].join(", "));
+        assert!(footer.lines().all(|line| line.len() <= super::WRAP_WIDTH));
+        assert!(footer.lines().skip(1).all(|line| line.starts_with("  ")));
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let commit = parse_commit_message("abc1234", "learned(api): x\n\nMemory: y\nSigned-off-by: Ada", Utc::now())
+            .unwrap().unwrap();
+        let json = serde_json::to_string(&commit).unwrap();
+        let decoded: crate::SvcmsCommit = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, commit);
+    }
+}